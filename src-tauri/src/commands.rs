@@ -1,12 +1,19 @@
 use crate::error::ConversionError;
-use crate::modules::{ffmpeg, naming, thumbnail};
+use crate::modules::validation::{self, ValidatedSettings};
+use crate::modules::{
+    chapters, exif, ffmpeg, ffmpeg_errors, job_log, metadata, naming, presets, scanner,
+    source_action, temp_files, thumbnail, verification, watcher,
+};
 use crate::state::AppState;
 use crate::types::{
-    parse_output_format, ConversionConfig, ConversionRequest, ConversionResult, FileInfo,
-    FileInfoResult, MediaType, OutputFormat, ProcessStatus, ProgressEvent, ThumbnailRequest,
-    ThumbnailResult,
+    ChapterMode, CommandPreview, ConflictMode, ConflictResolution, ConversionConfig,
+    ConversionPreset, ConversionRequest, ConversionResult, ConversionStats, CoverArtSource,
+    FileInfo, FileInfoResult, FileMetadata, MediaType, OutputFormat, OversizeConfig,
+    OversizePolicy, OversizeReport, PresetImportReport, PresetSettings, ProcessStatus,
+    ProgressEvent, ScanChunk, ScanRequest, ScanSummary, ThumbnailRequest, ThumbnailResult,
+    VerificationReport, WatchFolder, WatchFolderSettings,
 };
-use std::collections::VecDeque;
+use std::ffi::OsString;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use tauri::{AppHandle, Emitter, State};
@@ -20,15 +27,21 @@ use std::sync::OnceLock;
 
 static FFMPEG_PATH: OnceLock<PathBuf> = OnceLock::new();
 
-fn get_ffmpeg_path() -> Result<&'static Path, String> {
+// Share of a target size given to the streams; the rest covers muxing overhead.
+const TARGET_SIZE_HEADROOM: f64 = 0.95;
+const MIN_TARGET_VIDEO_KBPS: f64 = 100.0;
+
+fn get_ffmpeg_path() -> Result<&'static Path, ConversionError> {
     if let Some(path) = FFMPEG_PATH.get() {
         return Ok(path.as_path());
     }
 
-    let exe_dir = std::env::current_exe()
-        .map_err(|e| format!("Failed to get current exe path: {}", e))?
+    let exe_path = std::env::current_exe()?;
+    let exe_dir = exe_path
         .parent()
-        .ok_or("Failed to get exe parent dir")?
+        .ok_or_else(|| ConversionError::IoError {
+            message: "Failed to get exe parent dir".to_string(),
+        })?
         .to_path_buf();
 
     let exe_names = vec!["ffmpeg-x86_64-pc-windows-msvc.exe", "ffmpeg.exe"];
@@ -58,10 +71,12 @@ fn get_ffmpeg_path() -> Result<&'static Path, String> {
         }
     }
 
-    Err(format!(
-        "Could not find ffmpeg binary. Checked: {:?}",
-        checked
-    ))
+    Err(ConversionError::FfmpegNotFound {
+        checked: checked
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect(),
+    })
 }
 
 #[tauri::command]
@@ -69,162 +84,93 @@ pub async fn convert_file(
     app: AppHandle,
     state: State<'_, AppState>,
     request: ConversionRequest,
-) -> Result<ConversionResult, String> {
+) -> Result<ConversionResult, ConversionError> {
+    run_job(app, &state, request).await
+}
+
+// Runs one conversion as part of the current batch.
+async fn run_job(
+    app: AppHandle,
+    state: &AppState,
+    request: ConversionRequest,
+) -> Result<ConversionResult, ConversionError> {
+    state.begin_job().await;
+    let result = run_conversion(app.clone(), state, request).await;
+    if let Some(summary) = state.finish_job(&result).await {
+        let _ = app.emit("batch-summary", summary);
+    }
+    result
+}
+
+/// Runs one conversion outside the batch summary. The watch-folder worker
+/// reports its jobs through its own events instead.
+pub async fn run_conversion(
+    app: AppHandle,
+    state: &AppState,
+    request: ConversionRequest,
+) -> Result<ConversionResult, ConversionError> {
+    let started = std::time::Instant::now();
+    let mut request = presets::resolve_request(&app, request).await?;
     let file_id = request.id.clone();
     let input_path = PathBuf::from(&request.input_path);
 
     if !tokio::fs::try_exists(&input_path).await.unwrap_or(false) {
         return Err(ConversionError::FileNotFound {
             path: request.input_path.clone(),
-        }
-        .to_string());
+        });
     }
 
-    let output_format = parse_output_format(&request.output_format)
-        .ok_or_else(|| format!("Unsupported output format: {}", request.output_format))?;
+    let settings = validation::validate_request(&request)?;
+    let resolved = resolve_output_path(&app, &request, &settings, &input_path).await?;
 
-    let output_path = calculate_output_path(
-        &input_path,
-        &output_format,
-        request.output_directory.as_deref(),
-        request.naming_config.as_ref(),
-    );
+    if let ConflictResolution::Skipped { existing_path } = resolved.conflict {
+        return Ok(skip_existing(&app, file_id, existing_path));
+    }
 
-    let final_output_path = if !request.processing_enabled {
-        if let Some(stem) = output_path.file_stem() {
-            let ext = input_path.extension().unwrap_or_default().to_string_lossy();
-            output_path.with_file_name(format!("{}.{}", stem.to_string_lossy(), ext))
-        } else {
-            output_path.clone()
-        }
-    } else {
-        output_path.clone()
-    };
-
-    let final_output_path = if tokio::fs::try_exists(&final_output_path)
-        .await
-        .unwrap_or(false)
-    {
-        match request.conflict_mode.as_str() {
-            "overwrite" => final_output_path,
-            "keep_both" => {
-                let stem = final_output_path
-                    .file_stem()
-                    .map(|s| s.to_string_lossy().to_string())
-                    .unwrap_or_else(|| "output".to_string());
-                let ext = final_output_path
-                    .extension()
-                    .map(|s| s.to_string_lossy().to_string())
-                    .unwrap_or_default();
-                let parent = final_output_path
-                    .parent()
-                    .unwrap_or(Path::new("."))
-                    .to_path_buf();
-
-                let mut version = 2u32;
-                loop {
-                    let candidate = parent.join(format!("{}_{}.{}", stem, version, ext));
-                    if !tokio::fs::try_exists(&candidate).await.unwrap_or(false) {
-                        break candidate;
-                    }
-                    version += 1;
-                    if version > 9999 {
-                        return Err("Too many duplicate files".to_string());
-                    }
-                }
-            }
-            _ => {
-                return Err(ConversionError::FileConflict {
-                    path: final_output_path.to_string_lossy().to_string(),
-                }
-                .to_string());
-            }
-        }
-    } else {
-        final_output_path
-    };
+    let final_output_path = resolved.path.clone();
+    let temp_output_path = temp_files::output_path(&final_output_path, &file_id);
+    let mut temp_guard = temp_files::TempFiles::new(&app, &file_id);
 
     if let Some(parent) = final_output_path.parent() {
         tokio::fs::create_dir_all(parent)
             .await
-            .map_err(|e| e.to_string())?;
+            .map_err(|e| ConversionError::from_io(e, parent))?;
     }
 
     if !request.processing_enabled {
-        state
-            .register_output_path(file_id.clone(), final_output_path.clone())
-            .await;
-
-        let _ = app.emit(
-            "conversion-progress",
-            ProgressEvent {
-                id: file_id.clone(),
-                progress: 0,
-                status: ProcessStatus::Processing,
-                message: Some("Renaming/Copying...".to_string()),
-            },
-        );
+        return copy_source(&app, state, &request, &settings, resolved, started, None).await;
+    }
 
-        match tokio::fs::copy(&input_path, &final_output_path).await {
-            Ok(_) => {
-                state.remove_output_path(&file_id).await;
-                let _ = app.emit(
-                    "conversion-complete",
-                    ConversionResult {
-                        id: file_id.clone(),
-                        success: true,
-                        output_path: Some(final_output_path.clone()),
-                        error_message: None,
-                    },
-                );
-                return Ok(ConversionResult {
-                    id: file_id,
-                    success: true,
-                    output_path: Some(final_output_path),
-                    error_message: None,
-                });
-            }
-            Err(e) => {
-                state.remove_output_path(&file_id).await;
-                let err_msg = format!("Failed to copy file: {}", e);
-                let _ = app.emit(
-                    "conversion-complete",
-                    ConversionResult {
-                        id: file_id.clone(),
-                        success: false,
-                        output_path: None,
-                        error_message: Some(err_msg.clone()),
-                    },
-                );
-                return Err(err_msg);
-            }
-        }
+    apply_target_size(&mut request, &settings, &input_path).await?;
+    let metadata_tags = output_metadata_tags(&request, &input_path).await?;
+    temp_guard.track(temp_output_path.clone());
+    if matches!(request.cover_art, Some(CoverArtSource::VideoFrame { .. })) {
+        temp_guard.track(metadata::cover_frame_path(&file_id));
     }
+    if matches!(request.chapters, ChapterMode::Import { .. }) {
+        temp_guard.track(chapters::ffmetadata_path(&file_id));
+    }
+    let cover_art_path = prepare_cover_art(&request, &input_path).await?;
+    let chapters_file = prepare_chapters(&request, &input_path).await?;
+    let config = build_conversion_config(
+        &request,
+        &settings,
+        &input_path,
+        &temp_output_path,
+        metadata_tags,
+        cover_art_path,
+        chapters_file,
+    );
+    let args = ffmpeg::build_args(&config);
 
-    let config = ConversionConfig {
-        input_path: input_path.clone(),
-        output_path: final_output_path.clone(),
-        output_format: output_format.clone(),
-        quality_value: request.quality_value,
-        resize_config: request.resize_config.clone(),
-        is_muted: request.is_muted,
-        strip_metadata: request.strip_metadata,
-        conflict_mode: request.conflict_mode.clone(),
-        max_bitrate: request.max_bitrate,
-        video_preset: request
-            .video_preset
-            .clone()
-            .unwrap_or_else(|| "medium".to_string()),
-    };
+    let sidecar_path = get_ffmpeg_path()?;
 
-    let args = match &output_format {
-        OutputFormat::Video(_) => ffmpeg::build_video_args(&config),
-        OutputFormat::Audio(_) => ffmpeg::build_audio_extract_args(&config),
-        OutputFormat::Image(_) => ffmpeg::build_image_args(&config),
-    };
+    let mut job_log = job_log::JobLog::create(&app, &file_id).await.ok();
+    if let Some(log) = job_log.as_mut() {
+        log.write_command(sidecar_path, &args);
+    }
 
-    let sidecar_path = get_ffmpeg_path()?;
-    let mut cmd = Command::new(&sidecar_path);
+    let mut cmd = Command::new(sidecar_path);
     cmd.args(&args);
     cmd.stdout(Stdio::null());
     cmd.stderr(Stdio::piped());
@@ -232,16 +178,31 @@ pub async fn convert_file(
     #[cfg(windows)]
     cmd.creation_flags(CREATE_NO_WINDOW);
 
-    let mut child = cmd
-        .spawn()
-        .map_err(|e| format!("Failed to spawn ffmpeg: {}", e))?;
-
-    let stderr = child.stderr.take().ok_or("Failed to capture stderr")?;
+    let mut child = cmd.spawn().map_err(|e| {
+        let err = ConversionError::ProcessError {
+            tool: "ffmpeg".to_string(),
+            exit_code: None,
+            stderr: format!("Failed to spawn ffmpeg: {}", e),
+        };
+        if let Some(log) = job_log.as_mut() {
+            log.write_line(&format!("# {}", err));
+        }
+        err
+    })?;
+
+    let stderr = child
+        .stderr
+        .take()
+        .ok_or_else(|| ConversionError::ProcessError {
+            tool: "ffmpeg".to_string(),
+            exit_code: None,
+            stderr: "Failed to capture stderr".to_string(),
+        })?;
 
     state.register_process(file_id.clone(), child).await;
 
     state
-        .register_output_path(file_id.clone(), final_output_path.clone())
+        .register_temp_path(file_id.clone(), temp_output_path.clone())
         .await;
 
     let _ = app.emit(
@@ -257,13 +218,17 @@ pub async fn convert_file(
     let mut reader = BufReader::new(stderr).lines();
     let mut duration_secs: Option<f64> = None;
     let mut last_emit = std::time::Instant::now();
-    let mut last_log_lines: VecDeque<String> = VecDeque::with_capacity(20);
+    let mut log_lines: Vec<String> = Vec::new();
+    let mut temp_hidden = false;
 
     while let Ok(Some(line)) = reader.next_line().await {
-        if last_log_lines.len() >= 20 {
-            last_log_lines.pop_front();
+        if let Some(log) = job_log.as_mut() {
+            log.write_line(&line);
+        }
+
+        if !ffmpeg::is_progress_line(&line) {
+            log_lines.push(line.clone());
         }
-        last_log_lines.push_back(line.clone());
 
         if duration_secs.is_none() {
             if let Some(d) = ffmpeg::parse_duration(&line) {
@@ -272,6 +237,10 @@ pub async fn convert_file(
         }
 
         if let Some(progress) = ffmpeg::parse_progress(&line, duration_secs) {
+            // ffmpeg has created the output by the time it reports progress.
+            if !temp_hidden {
+                temp_hidden = temp_files::set_hidden(&temp_output_path, true);
+            }
             if last_emit.elapsed() >= std::time::Duration::from_millis(100) {
                 let _ = app.emit(
                     "conversion-progress",
@@ -288,81 +257,919 @@ pub async fn convert_file(
     }
 
     if let Some(mut child) = state.remove_process(&file_id).await {
-        let status = child
-            .wait()
+        let status = child.wait().await?;
+
+        if let Some(log) = job_log.take() {
+            log.finish(status.code());
+        }
+
+        let mut verification = None;
+        let checked = if !status.success() {
+            discard_temp_output(state, &file_id).await;
+            Err(ffmpeg_errors::classify_failure(
+                &log_lines.join("\n"),
+                status.code(),
+                &input_path,
+                &final_output_path,
+            ))
+        } else if request.verify_output {
+            let _ = app.emit(
+                "conversion-progress",
+                ProgressEvent {
+                    id: file_id.clone(),
+                    progress: 100,
+                    status: ProcessStatus::Processing,
+                    message: Some("Verifying output...".to_string()),
+                },
+            );
+            match verify_conversion(&config).await {
+                Ok(report) if report.passed => {
+                    verification = Some(report);
+                    Ok(())
+                }
+                Ok(report) => {
+                    discard_temp_output(state, &file_id).await;
+                    let err = ConversionError::VerificationFailed {
+                        path: final_output_path.to_string_lossy().to_string(),
+                        failures: report.failures(),
+                    };
+                    verification = Some(report);
+                    Err(err)
+                }
+                Err(e) => {
+                    discard_temp_output(state, &file_id).await;
+                    Err(e)
+                }
+            }
+        } else {
+            Ok(())
+        };
+
+        let oversize = match checked {
+            Ok(()) => {
+                check_oversize(
+                    request.oversize_config.as_ref(),
+                    &input_path,
+                    &temp_output_path,
+                )
+                .await
+            }
+            Err(_) => None,
+        };
+
+        let committed = match (checked, oversize.as_ref().map(|o| o.policy)) {
+            (Err(err), _) => Err(err),
+            (Ok(()), Some(OversizePolicy::CopyOriginal | OversizePolicy::KeepOriginalFormat)) => {
+                discard_temp_output(state, &file_id).await;
+                let named_path = match &resolved.conflict {
+                    ConflictResolution::Renamed { original_path } => original_path,
+                    _ => &resolved.path,
+                };
+                let copy_path = with_source_extension(named_path, &input_path);
+                let copy_output =
+                    resolve_conflict(&request, &settings, &input_path, copy_path).await?;
+                if let ConflictResolution::Skipped { existing_path } = copy_output.conflict {
+                    return Ok(skip_existing(&app, file_id, existing_path));
+                }
+                return copy_source(
+                    &app,
+                    state,
+                    &request,
+                    &settings,
+                    copy_output,
+                    started,
+                    oversize,
+                )
+                .await;
+            }
+            (Ok(()), _) => {
+                finish_temp_output(state, &request, &settings, &temp_output_path, &resolved).await
+            }
+        };
+
+        match committed {
+            Ok(CommitOutcome::Kept(existing_path)) => {
+                Ok(skip_existing(&app, file_id, existing_path))
+            }
+            Err(err) => {
+                let _ = app.emit(
+                    "conversion-complete",
+                    ConversionResult {
+                        id: file_id.clone(),
+                        success: false,
+                        skipped: false,
+                        output_path: None,
+                        error_message: Some(err.to_string()),
+                        verification,
+                        stats: None,
+                        oversize: None,
+                        source_action: None,
+                    },
+                );
+                Err(err)
+            }
+            Ok(CommitOutcome::Written(final_output_path)) => {
+                let stats = collect_stats(&input_path, &final_output_path, started).await;
+                let source_action =
+                    source_action::apply(&request.source_action, &input_path, &final_output_path)
+                        .await;
+                let _ = app.emit(
+                    "conversion-complete",
+                    ConversionResult {
+                        id: file_id.clone(),
+                        success: true,
+                        skipped: false,
+                        output_path: Some(final_output_path.clone()),
+                        error_message: None,
+                        verification: verification.clone(),
+                        stats: stats.clone(),
+                        oversize: oversize.clone(),
+                        source_action: source_action.clone(),
+                    },
+                );
+                Ok(ConversionResult {
+                    id: file_id,
+                    success: true,
+                    skipped: false,
+                    output_path: Some(final_output_path),
+                    error_message: None,
+                    verification,
+                    stats,
+                    oversize,
+                    source_action,
+                })
+            }
+        }
+    } else {
+        if let Some(mut log) = job_log.take() {
+            log.write_line("# cancelled by user");
+            log.finish(None);
+        }
+
+        let _ = app.emit(
+            "conversion-progress",
+            ProgressEvent {
+                id: file_id.clone(),
+                progress: 0,
+                status: ProcessStatus::Cancelled,
+                message: Some("Cancelled by user".to_string()),
+            },
+        );
+        Err(ConversionError::Cancelled { id: file_id })
+    }
+}
+
+fn skip_existing(app: &AppHandle, id: String, existing_path: PathBuf) -> ConversionResult {
+    let _ = app.emit(
+        "conversion-progress",
+        ProgressEvent {
+            id: id.clone(),
+            progress: 0,
+            status: ProcessStatus::Conflict,
+            message: Some(format!(
+                "Skipped: {} already exists",
+                existing_path.to_string_lossy()
+            )),
+        },
+    );
+    ConversionResult {
+        id,
+        success: false,
+        skipped: true,
+        output_path: Some(existing_path),
+        error_message: None,
+        verification: None,
+        stats: None,
+        oversize: None,
+        source_action: None,
+    }
+}
+
+struct ResolvedOutput {
+    path: PathBuf,
+    conflict: ConflictResolution,
+}
+
+async fn resolve_output_path(
+    app: &AppHandle,
+    request: &ConversionRequest,
+    settings: &ValidatedSettings,
+    input_path: &Path,
+) -> Result<ResolvedOutput, ConversionError> {
+    let naming_context = match &request.naming_config {
+        Some(config) => Some(naming_context(app, request, settings, input_path, config).await),
+        None => None,
+    };
+    let output_directory = output_directory(request, input_path)?;
+    let output_path = calculate_output_path(
+        input_path,
+        &settings.output_format,
+        output_directory.as_deref(),
+        request.naming_config.as_ref().zip(naming_context.as_ref()),
+    );
+
+    let output_path = if !request.processing_enabled {
+        with_source_extension(&output_path, input_path)
+    } else {
+        output_path
+    };
+    resolve_conflict(request, settings, input_path, output_path).await
+}
+
+// The source keeps its own extension when it is copied instead of converted.
+fn with_source_extension(output_path: &Path, input_path: &Path) -> PathBuf {
+    match output_path.file_stem() {
+        Some(stem) => {
+            let ext = input_path.extension().unwrap_or_default().to_string_lossy();
+            output_path.with_file_name(format!("{}.{}", stem.to_string_lossy(), ext))
+        }
+        None => output_path.to_path_buf(),
+    }
+}
+
+async fn resolve_conflict(
+    request: &ConversionRequest,
+    settings: &ValidatedSettings,
+    input_path: &Path,
+    output_path: PathBuf,
+) -> Result<ResolvedOutput, ConversionError> {
+    if !tokio::fs::try_exists(&output_path).await.unwrap_or(false) {
+        return Ok(ResolvedOutput {
+            path: output_path,
+            conflict: ConflictResolution::None,
+        });
+    }
+
+    let should_overwrite = match request.conflict_mode {
+        ConflictMode::Skip => false,
+        ConflictMode::Overwrite => true,
+        ConflictMode::OverwriteIfOlder => {
+            file_modified(&output_path).await? < file_modified(input_path).await?
+        }
+        // Settled against the finished output in `commit_temp_output`.
+        ConflictMode::OverwriteIfLarger | ConflictMode::OverwriteIfSmaller => true,
+        ConflictMode::KeepBoth => {
+            return find_free_path(output_path, &settings.conflict_suffix).await;
+        }
+    };
+
+    let conflict = if should_overwrite {
+        ConflictResolution::Overwrite
+    } else {
+        ConflictResolution::Skipped {
+            existing_path: output_path.clone(),
+        }
+    };
+    Ok(ResolvedOutput {
+        path: output_path,
+        conflict,
+    })
+}
+
+async fn find_free_path(
+    output_path: PathBuf,
+    suffix_pattern: &str,
+) -> Result<ResolvedOutput, ConversionError> {
+    let stem = output_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "output".to_string());
+    let ext = output_path
+        .extension()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let parent = output_path.parent().unwrap_or(Path::new(".")).to_path_buf();
+
+    for version in 2u32..=9999 {
+        let suffix = suffix_pattern.replace("{n}", &version.to_string());
+        let candidate = parent.join(format!("{}{}.{}", stem, suffix, ext));
+        if !tokio::fs::try_exists(&candidate).await.unwrap_or(false) {
+            return Ok(ResolvedOutput {
+                path: candidate,
+                conflict: ConflictResolution::Renamed {
+                    original_path: output_path,
+                },
+            });
+        }
+    }
+
+    Err(ConversionError::TooManyDuplicates {
+        path: output_path.to_string_lossy().to_string(),
+    })
+}
+
+async fn file_modified(path: &Path) -> Result<std::time::SystemTime, ConversionError> {
+    tokio::fs::metadata(path)
+        .await
+        .and_then(|m| m.modified())
+        .map_err(|e| ConversionError::from_io(e, path))
+}
+
+async fn file_size(path: &Path) -> Result<u64, ConversionError> {
+    tokio::fs::metadata(path)
+        .await
+        .map(|m| m.len())
+        .map_err(|e| ConversionError::from_io(e, path))
+}
+
+fn build_conversion_config(
+    request: &ConversionRequest,
+    settings: &ValidatedSettings,
+    input_path: &Path,
+    output_path: &Path,
+    metadata_tags: Vec<(String, String)>,
+    cover_art_path: Option<PathBuf>,
+    chapters_file: Option<PathBuf>,
+) -> ConversionConfig {
+    ConversionConfig {
+        input_path: input_path.to_path_buf(),
+        output_path: output_path.to_path_buf(),
+        output_format: settings.output_format.clone(),
+        quality: settings.quality,
+        resize_config: request.resize_config.clone(),
+        is_muted: request.is_muted,
+        strip_metadata: request.strip_metadata || request.keep_metadata.is_some(),
+        metadata_tags,
+        cover_art_path,
+        keep_chapters: match request.chapters {
+            ChapterMode::Auto => !request.strip_metadata && request.keep_metadata.is_none(),
+            ChapterMode::Strip => false,
+            ChapterMode::Keep | ChapterMode::Import { .. } => true,
+        },
+        chapters_file,
+        // ffmpeg only ever writes to our own temp file, which is renamed over
+        // the destination afterwards, so letting it overwrite is always safe.
+        overwrite: true,
+        max_bitrate: request.max_bitrate,
+        video_preset: settings.video_preset,
+        extra_input_args: request.extra_input_args.clone(),
+        extra_output_args: request.extra_output_args.clone(),
+    }
+}
+
+#[tauri::command]
+pub async fn preview_command(
+    app: AppHandle,
+    request: ConversionRequest,
+) -> Result<CommandPreview, ConversionError> {
+    let mut request = presets::resolve_request(&app, request).await?;
+    let input_path = PathBuf::from(&request.input_path);
+
+    if !tokio::fs::try_exists(&input_path).await.unwrap_or(false) {
+        return Err(ConversionError::FileNotFound {
+            path: request.input_path.clone(),
+        });
+    }
+
+    let settings = validation::validate_request(&request)?;
+    let resolved = resolve_output_path(&app, &request, &settings, &input_path).await?;
+
+    if !request.processing_enabled
+        || matches!(resolved.conflict, ConflictResolution::Skipped { .. })
+    {
+        return Ok(CommandPreview {
+            output_path: resolved.path,
+            conflict: resolved.conflict,
+            program: None,
+            args: Vec::new(),
+            command_line: None,
+        });
+    }
+
+    apply_target_size(&mut request, &settings, &input_path).await?;
+    // Shown with the final path rather than the temp file a real job writes
+    // to, so a copied command produces the same output.
+    let metadata_tags = output_metadata_tags(&request, &input_path).await?;
+    let mut config = build_conversion_config(
+        &request,
+        &settings,
+        &input_path,
+        &resolved.path,
+        metadata_tags,
+        request
+            .cover_art
+            .as_ref()
+            .map(|c| metadata::cover_art_path(c, &request.id)),
+        matches!(request.chapters, ChapterMode::Import { .. })
+            .then(|| chapters::ffmetadata_path(&request.id)),
+    );
+    config.overwrite = matches!(resolved.conflict, ConflictResolution::Overwrite);
+    let args = ffmpeg::build_args(&config);
+    let program = get_ffmpeg_path()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| "ffmpeg".to_string());
+
+    Ok(CommandPreview {
+        output_path: resolved.path,
+        conflict: resolved.conflict,
+        command_line: Some(ffmpeg::format_command_line(Path::new(&program), &args)),
+        program: Some(program),
+        args: args
+            .iter()
+            .map(|a| a.to_string_lossy().to_string())
+            .collect(),
+    })
+}
+
+// Applies the finishing touches that need the complete file, then moves it
+// into place.
+async fn finish_temp_output(
+    state: &AppState,
+    request: &ConversionRequest,
+    settings: &ValidatedSettings,
+    temp_path: &Path,
+    output: &ResolvedOutput,
+) -> Result<CommitOutcome, ConversionError> {
+    if request.preserve_timestamps {
+        if let Err(err) = metadata::copy_file_times(Path::new(&request.input_path), temp_path).await
+        {
+            discard_temp_output(state, &request.id).await;
+            return Err(err);
+        }
+    }
+    commit_temp_output(state, request, settings, temp_path, output).await
+}
+
+// Caps the video bitrate so the output fits `target_size_kb`, leaving room
+// for the audio track and muxing overhead.
+async fn apply_target_size(
+    request: &mut ConversionRequest,
+    settings: &ValidatedSettings,
+    input_path: &Path,
+) -> Result<(), ConversionError> {
+    let Some(target_kb) = request.target_size_kb else {
+        return Ok(());
+    };
+    if !matches!(settings.output_format, OutputFormat::Video(_)) {
+        return Ok(());
+    }
+    let (_, log) = run_ffmpeg_capture(&ffmpeg::build_probe_args(input_path)).await?;
+    let Some(duration_secs) = verification::parse_probe(&log)
+        .and_then(|p| p.duration_secs)
+        .filter(|d| *d > 0.0)
+    else {
+        return Ok(());
+    };
+
+    let audio_kbps = if request.is_muted {
+        0.0
+    } else {
+        ffmpeg::VIDEO_AUDIO_BITRATE_KBPS as f64
+    };
+    let total_kbps = target_kb as f64 * 8.0 * TARGET_SIZE_HEADROOM / duration_secs;
+    let video_kbps = (total_kbps - audio_kbps).max(MIN_TARGET_VIDEO_KBPS) as u64;
+    request.max_bitrate = Some(
+        request
+            .max_bitrate
+            .map_or(video_kbps, |max| max.min(video_kbps)),
+    );
+    Ok(())
+}
+
+// Whitelisted source tags followed by the tags set on the request, so the
+// latter win when ffmpeg sees the same key twice.
+async fn output_metadata_tags(
+    request: &ConversionRequest,
+    input_path: &Path,
+) -> Result<Vec<(String, String)>, ConversionError> {
+    let mut tags = match &request.keep_metadata {
+        Some(keep) => {
+            let (_, log) = run_ffmpeg_capture(&ffmpeg::build_probe_args(input_path)).await?;
+            metadata::filter_tags(&metadata::parse_global_tags(&log), keep)
+        }
+        None => Vec::new(),
+    };
+    tags.extend(
+        request
+            .set_metadata
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone())),
+    );
+    Ok(tags)
+}
+
+async fn prepare_cover_art(
+    request: &ConversionRequest,
+    input_path: &Path,
+) -> Result<Option<PathBuf>, ConversionError> {
+    let Some(cover) = &request.cover_art else {
+        return Ok(None);
+    };
+    let path = metadata::cover_art_path(cover, &request.id);
+
+    if let Some(time_secs) = metadata::cover_frame_time(cover) {
+        let args = ffmpeg::build_cover_frame_args(input_path, time_secs, &path);
+        let (exit_code, log) = run_ffmpeg_capture(&args).await?;
+        if exit_code != Some(0) || !tokio::fs::try_exists(&path).await.unwrap_or(false) {
+            return Err(ffmpeg_errors::classify_failure(
+                &log, exit_code, input_path, &path,
+            ));
+        }
+    } else if !tokio::fs::try_exists(&path).await.unwrap_or(false) {
+        return Err(ConversionError::FileNotFound {
+            path: path.to_string_lossy().to_string(),
+        });
+    }
+
+    Ok(Some(path))
+}
+
+async fn prepare_chapters(
+    request: &ConversionRequest,
+    input_path: &Path,
+) -> Result<Option<PathBuf>, ConversionError> {
+    let ChapterMode::Import { path } = &request.chapters else {
+        return Ok(None);
+    };
+
+    let (_, log) = run_ffmpeg_capture(&ffmpeg::build_probe_args(input_path)).await?;
+    let duration_secs = verification::parse_probe(&log).and_then(|p| p.duration_secs);
+    // Imported chapters are used as written: requests have no trim range yet,
+    // so there is nothing to clip or shift them against.
+    let imported = chapters::read_chapter_file(Path::new(path), duration_secs).await?;
+
+    let ffmetadata_path = chapters::ffmetadata_path(&request.id);
+    tokio::fs::write(&ffmetadata_path, chapters::to_ffmetadata(&imported))
+        .await
+        .map_err(|e| ConversionError::from_io(e, &ffmetadata_path))?;
+    Ok(Some(ffmetadata_path))
+}
+
+enum CommitOutcome {
+    Written(PathBuf),
+    // The file already at the destination won and the new output was dropped.
+    Kept(PathBuf),
+}
+
+async fn commit_temp_output(
+    state: &AppState,
+    request: &ConversionRequest,
+    settings: &ValidatedSettings,
+    temp_path: &Path,
+    output: &ResolvedOutput,
+) -> Result<CommitOutcome, ConversionError> {
+    let final_path = output.path.as_path();
+    // The temp path is only gone if the job was cancelled in the meantime.
+    if state.remove_temp_path(&request.id).await.is_none() {
+        return Err(ConversionError::Cancelled {
+            id: request.id.clone(),
+        });
+    }
+    temp_files::set_hidden(temp_path, false);
+    let keep_existing =
+        match keeps_existing_output(request.conflict_mode, temp_path, final_path).await {
+            Ok(keep) => keep,
+            Err(err) => {
+                let _ = tokio::fs::remove_file(temp_path).await;
+                return Err(err);
+            }
+        };
+    if keep_existing {
+        let _ = tokio::fs::remove_file(temp_path).await;
+        return Ok(CommitOutcome::Kept(final_path.to_path_buf()));
+    }
+
+    let committed = match request.conflict_mode {
+        // Another job may have taken the path since it was resolved, so these
+        // modes must never replace what is there now.
+        ConflictMode::Skip | ConflictMode::KeepBoth => {
+            move_without_replacing(request.conflict_mode, settings, temp_path, output).await
+        }
+        _ => tokio::fs::rename(temp_path, final_path)
             .await
-            .map_err(|e| format!("Failed to wait on child: {}", e))?;
+            .map(|_| CommitOutcome::Written(final_path.to_path_buf()))
+            .map_err(|e| ConversionError::from_io(e, final_path)),
+    };
+    if !matches!(committed, Ok(CommitOutcome::Written(_))) {
+        let _ = tokio::fs::remove_file(temp_path).await;
+    }
+    committed
+}
+
+// Keep-both picks the next free name when it loses the race, skip keeps the
+// file that got there first.
+async fn move_without_replacing(
+    mode: ConflictMode,
+    settings: &ValidatedSettings,
+    temp_path: &Path,
+    output: &ResolvedOutput,
+) -> Result<CommitOutcome, ConversionError> {
+    let original_path = match &output.conflict {
+        ConflictResolution::Renamed { original_path } => original_path,
+        _ => &output.path,
+    };
+    let mut target = output.path.clone();
+    loop {
+        match rename_no_replace(temp_path, &target).await {
+            Ok(()) => return Ok(CommitOutcome::Written(target)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(ConversionError::from_io(e, &target)),
+        }
+        if mode != ConflictMode::KeepBoth {
+            return Ok(CommitOutcome::Kept(target));
+        }
+        target = find_free_path(original_path.clone(), &settings.conflict_suffix)
+            .await?
+            .path;
+    }
+}
 
-        if status.success() {
-            state.remove_output_path(&file_id).await;
+// Creating a hard link fails atomically when the target exists. Filesystems
+// without hard links (FAT, some network shares) fall back to check-then-rename.
+async fn rename_no_replace(from: &Path, to: &Path) -> std::io::Result<()> {
+    match tokio::fs::hard_link(from, to).await {
+        Ok(()) => {
+            let _ = tokio::fs::remove_file(from).await;
+            Ok(())
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Err(e),
+        Err(_) if tokio::fs::try_exists(to).await.unwrap_or(false) => {
+            Err(std::io::ErrorKind::AlreadyExists.into())
+        }
+        Err(_) => tokio::fs::rename(from, to).await,
+    }
+}
+
+// The size-based modes compare the existing output with the new one, which
+// only exists once ffmpeg is done.
+async fn keeps_existing_output(
+    mode: ConflictMode,
+    temp_path: &Path,
+    final_path: &Path,
+) -> Result<bool, ConversionError> {
+    if !matches!(
+        mode,
+        ConflictMode::OverwriteIfLarger | ConflictMode::OverwriteIfSmaller
+    ) || !tokio::fs::try_exists(final_path).await.unwrap_or(false)
+    {
+        return Ok(false);
+    }
+    let existing_bytes = file_size(final_path).await?;
+    let new_bytes = file_size(temp_path).await?;
+    Ok(match mode {
+        ConflictMode::OverwriteIfLarger => existing_bytes <= new_bytes,
+        _ => existing_bytes >= new_bytes,
+    })
+}
+
+async fn run_ffmpeg_capture(args: &[OsString]) -> Result<(Option<i32>, String), ConversionError> {
+    let mut cmd = Command::new(get_ffmpeg_path()?);
+    cmd.args(args);
+    cmd.stdout(Stdio::null());
+
+    #[cfg(windows)]
+    cmd.creation_flags(CREATE_NO_WINDOW);
 
+    let output = cmd
+        .output()
+        .await
+        .map_err(|e| ConversionError::ProcessError {
+            tool: "ffmpeg".to_string(),
+            exit_code: None,
+            stderr: format!("Failed to spawn ffmpeg: {}", e),
+        })?;
+    Ok((
+        output.status.code(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+    ))
+}
+
+async fn verify_conversion(
+    config: &ConversionConfig,
+) -> Result<VerificationReport, ConversionError> {
+    let (_, input_log) = run_ffmpeg_capture(&ffmpeg::build_probe_args(&config.input_path)).await?;
+    let (_, output_log) =
+        run_ffmpeg_capture(&ffmpeg::build_probe_args(&config.output_path)).await?;
+    let (decode_exit_code, decode_log) =
+        run_ffmpeg_capture(&ffmpeg::build_decode_check_args(&config.output_path)).await?;
+    Ok(verification::verify(
+        config,
+        &input_log,
+        &output_log,
+        decode_exit_code,
+        &decode_log,
+    ))
+}
+
+// Places a copy of the source at the output path, used when processing is
+// disabled or when an oversized output is reverted to the original.
+async fn copy_source(
+    app: &AppHandle,
+    state: &AppState,
+    request: &ConversionRequest,
+    settings: &ValidatedSettings,
+    output: ResolvedOutput,
+    started: std::time::Instant,
+    oversize: Option<OversizeReport>,
+) -> Result<ConversionResult, ConversionError> {
+    let file_id = request.id.clone();
+    let input_path = Path::new(&request.input_path);
+    let temp_output_path = temp_files::output_path(&output.path, &file_id);
+    // Separate from the conversion's guard, which may still be alive.
+    let mut temp_guard = temp_files::TempFiles::new(app, &format!("{}-copy", file_id));
+    temp_guard.track(temp_output_path.clone());
+    state
+        .register_temp_path(file_id.clone(), temp_output_path.clone())
+        .await;
+
+    let _ = app.emit(
+        "conversion-progress",
+        ProgressEvent {
+            id: file_id.clone(),
+            progress: 0,
+            status: ProcessStatus::Processing,
+            message: Some("Renaming/Copying...".to_string()),
+        },
+    );
+
+    let remux = oversize
+        .as_ref()
+        .is_some_and(|o| o.policy == OversizePolicy::KeepOriginalFormat);
+    let copied = match place_source(request, &temp_output_path, &output.path, remux).await {
+        Ok(()) => {
+            temp_files::set_hidden(&temp_output_path, true);
+            finish_temp_output(state, request, settings, &temp_output_path, &output).await
+        }
+        Err(err) => {
+            discard_temp_output(state, &file_id).await;
+            Err(err)
+        }
+    };
+
+    match copied {
+        Ok(CommitOutcome::Kept(existing_path)) => Ok(skip_existing(app, file_id, existing_path)),
+        Ok(CommitOutcome::Written(final_output_path)) => {
+            let stats = collect_stats(input_path, &final_output_path, started).await;
+            let source_action =
+                source_action::apply(&request.source_action, input_path, &final_output_path).await;
             let _ = app.emit(
                 "conversion-complete",
                 ConversionResult {
                     id: file_id.clone(),
                     success: true,
+                    skipped: false,
                     output_path: Some(final_output_path.clone()),
                     error_message: None,
+                    verification: None,
+                    stats: stats.clone(),
+                    oversize: oversize.clone(),
+                    source_action: source_action.clone(),
                 },
             );
             Ok(ConversionResult {
                 id: file_id,
                 success: true,
+                skipped: false,
                 output_path: Some(final_output_path),
                 error_message: None,
+                verification: None,
+                stats,
+                oversize,
+                source_action,
             })
-        } else {
-            if let Some(partial_path) = state.remove_output_path(&file_id).await {
-                if partial_path.exists() {
-                    let _ = tokio::fs::remove_file(&partial_path).await;
-                }
-            }
-
-            let log_content: String = last_log_lines.make_contiguous().join("\n");
-            let err_msg = if log_content.contains("Output file does not contain any stream") {
-                "Conversion failed: Input file has no suitable audio stream.".to_string()
-            } else if log_content.contains("Permission denied") {
-                "Conversion failed: Permission denied writing to output.".to_string()
-            } else if log_content.contains("No space left on device") {
-                "Conversion failed: Disk full.".to_string()
-            } else {
-                format!(
-                    "FFmpeg exited with code: {:?}\n{}",
-                    status.code(),
-                    log_content
-                )
-            };
-
+        }
+        Err(err) => {
             let _ = app.emit(
                 "conversion-complete",
                 ConversionResult {
                     id: file_id.clone(),
                     success: false,
+                    skipped: false,
                     output_path: None,
-                    error_message: Some(err_msg.clone()),
+                    error_message: Some(format!("Failed to copy file: {}", err)),
+                    verification: None,
+                    stats: None,
+                    oversize: None,
+                    source_action: None,
                 },
             );
-            Err(err_msg)
+            Err(err)
+        }
+    }
+}
+
+// A remux keeps the source's streams and container but still honours
+// `strip_metadata`; otherwise the bytes are copied as they are.
+async fn place_source(
+    request: &ConversionRequest,
+    temp_path: &Path,
+    final_path: &Path,
+    remux: bool,
+) -> Result<(), ConversionError> {
+    let input_path = Path::new(&request.input_path);
+    if !remux {
+        return tokio::fs::copy(input_path, temp_path)
+            .await
+            .map(|_| ())
+            .map_err(|e| ConversionError::from_io(e, final_path));
+    }
+    let args = ffmpeg::build_remux_args(input_path, temp_path, request.strip_metadata);
+    let (exit_code, log) = run_ffmpeg_capture(&args).await?;
+    if exit_code != Some(0) {
+        return Err(ffmpeg_errors::classify_failure(
+            &log, exit_code, input_path, final_path,
+        ));
+    }
+    Ok(())
+}
+
+async fn check_oversize(
+    config: Option<&OversizeConfig>,
+    input_path: &Path,
+    output_path: &Path,
+) -> Option<OversizeReport> {
+    let config = config?;
+    let input_bytes = file_size(input_path).await.ok()?;
+    let output_bytes = file_size(output_path).await.ok()?;
+    let limit = input_bytes as f64 * (1.0 + config.threshold_percent / 100.0);
+
+    (output_bytes as f64 > limit).then_some(OversizeReport {
+        policy: config.policy,
+        threshold_percent: config.threshold_percent,
+        input_bytes,
+        output_bytes,
+    })
+}
+
+async fn collect_stats(
+    input_path: &Path,
+    output_path: &Path,
+    started: std::time::Instant,
+) -> Option<ConversionStats> {
+    let elapsed_secs = started.elapsed().as_secs_f64();
+    let input_bytes = file_size(input_path).await.ok()?;
+    let output_bytes = file_size(output_path).await.ok()?;
+    let probe = match run_ffmpeg_capture(&ffmpeg::build_probe_args(output_path)).await {
+        Ok((_, log)) => verification::parse_probe(&log).unwrap_or_default(),
+        Err(_) => verification::MediaProbe::default(),
+    };
+
+    Some(ConversionStats {
+        input_bytes,
+        output_bytes,
+        compression_ratio: (input_bytes > 0).then(|| output_bytes as f64 / input_bytes as f64),
+        elapsed_ms: (elapsed_secs * 1000.0) as u64,
+        speed: probe
+            .duration_secs
+            .filter(|_| elapsed_secs > 0.0)
+            .map(|d| d / elapsed_secs),
+        width: probe.width,
+        height: probe.height,
+        duration_secs: probe.duration_secs,
+    })
+}
+
+async fn discard_temp_output(state: &AppState, id: &str) {
+    if let Some(temp_path) = state.remove_temp_path(id).await {
+        if temp_path.exists() {
+            let _ = tokio::fs::remove_file(&temp_path).await;
         }
-    } else {
-        let _ = app.emit(
-            "conversion-progress",
-            ProgressEvent {
-                id: file_id.clone(),
-                progress: 0,
-                status: ProcessStatus::Cancelled,
-                message: Some("Cancelled by user".to_string()),
-            },
-        );
-        Err("Conversion cancelled".to_string())
     }
 }
 
+// With `preserve_structure`, the input's folder relative to the base directory
+// is recreated under the output directory.
+fn output_directory(
+    request: &ConversionRequest,
+    input_path: &Path,
+) -> Result<Option<PathBuf>, ConversionError> {
+    let Some(dir) = request.output_directory.as_deref() else {
+        return Ok(None);
+    };
+    let output_dir = PathBuf::from(dir);
+    if !request.preserve_structure {
+        return Ok(Some(output_dir));
+    }
+
+    let base = request
+        .base_directory
+        .as_deref()
+        .ok_or_else(|| ConversionError::InvalidConfig {
+            message: "base_directory: required when preserve_structure is set".to_string(),
+        })?;
+    // `strip_prefix` compares components as written, so `./videos`, `..` or a
+    // symlinked base would never match without resolving both sides first.
+    let canonical =
+        |path: &Path| std::fs::canonicalize(path).map_err(|e| ConversionError::from_io(e, path));
+    let parent = canonical(input_path.parent().unwrap_or(Path::new(".")))?;
+    let relative = parent
+        .strip_prefix(canonical(Path::new(base))?)
+        .map_err(|_| ConversionError::InvalidConfig {
+            message: format!(
+                "base_directory: {} is not inside {}",
+                input_path.display(),
+                base
+            ),
+        })?
+        .to_path_buf();
+    Ok(Some(output_dir.join(relative)))
+}
+
 fn calculate_output_path(
     input_path: &Path,
     output_format: &OutputFormat,
-    output_directory: Option<&str>,
-    naming_config: Option<&crate::types::NamingConfig>,
+    output_directory: Option<&Path>,
+    naming: Option<(&crate::types::NamingConfig, &naming::NamingContext)>,
 ) -> PathBuf {
     let input_stem = input_path
         .file_stem()
@@ -370,13 +1177,13 @@ fn calculate_output_path(
         .unwrap_or_else(|| "output".to_string());
 
     let output_dir = if let Some(dir) = output_directory {
-        PathBuf::from(dir)
+        dir.to_path_buf()
     } else {
         input_path.parent().unwrap_or(Path::new(".")).to_path_buf()
     };
 
-    let output_name = if let Some(config) = naming_config {
-        naming::apply_naming_pipeline(&input_stem, config)
+    let output_name = if let Some((config, ctx)) = naming {
+        naming::apply_naming_pipeline(ctx, config)
     } else {
         input_stem
     };
@@ -384,15 +1191,85 @@ fn calculate_output_path(
     output_dir.join(format!("{}.{}", output_name, output_format.extension()))
 }
 
+// Probing is only done when a naming block needs dimensions, duration or tags.
+async fn naming_context(
+    app: &AppHandle,
+    request: &ConversionRequest,
+    settings: &ValidatedSettings,
+    input_path: &Path,
+    config: &crate::types::NamingConfig,
+) -> naming::NamingContext {
+    let lossy = |s: Option<&std::ffi::OsStr>| s.map(|s| s.to_string_lossy().to_string());
+    let mut ctx = naming::NamingContext {
+        original_stem: lossy(input_path.file_stem()).unwrap_or_else(|| "output".to_string()),
+        original_extension: lossy(input_path.extension()).unwrap_or_default(),
+        parent_folder: lossy(input_path.parent().and_then(Path::file_name)),
+        output_extension: settings.output_format.extension().to_string(),
+        preset_name: match &request.preset_id {
+            Some(id) => presets::get_preset(app, id).await.ok().map(|p| p.name),
+            None => None,
+        },
+        batch_index: request.batch_index.unwrap_or_default(),
+        ..Default::default()
+    };
+
+    if !naming::needs_media_info(config) {
+        return ctx;
+    }
+
+    if let Ok((_, log)) = run_ffmpeg_capture(&ffmpeg::build_probe_args(input_path)).await {
+        if let Some(probe) = verification::parse_probe(&log) {
+            ctx.width = probe.width;
+            ctx.height = probe.height;
+            ctx.duration_secs = probe.duration_secs;
+        }
+        ctx.tags = metadata::parse_global_tags(&log)
+            .into_iter()
+            .map(|(k, v)| (k.to_lowercase(), v))
+            .collect();
+    }
+
+    let is_image = input_path
+        .extension()
+        .and_then(|e| MediaType::from_extension(&e.to_string_lossy()))
+        == Some(MediaType::Image);
+    let exif = if is_image {
+        tokio::fs::read(input_path)
+            .await
+            .ok()
+            .and_then(|data| exif::read_exif(&data))
+    } else {
+        None
+    };
+
+    ctx.date_taken = exif
+        .as_ref()
+        .and_then(|e| e.date_taken.as_deref())
+        .or_else(|| ctx.tags.get("creation_time").map(String::as_str))
+        .and_then(naming::parse_metadata_date);
+    ctx.camera = exif.and_then(|e| {
+        let camera = [e.camera_make, e.camera_model]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ");
+        (!camera.is_empty()).then_some(camera)
+    });
+
+    ctx
+}
+
 #[tauri::command]
 pub async fn generate_thumbnail(
     _app: AppHandle,
     request: ThumbnailRequest,
-) -> Result<ThumbnailResult, String> {
+) -> Result<ThumbnailResult, ConversionError> {
     generate_single_thumbnail(request).await
 }
 
-async fn generate_single_thumbnail(request: ThumbnailRequest) -> Result<ThumbnailResult, String> {
+async fn generate_single_thumbnail(
+    request: ThumbnailRequest,
+) -> Result<ThumbnailResult, ConversionError> {
     let (args, output_path) = match request.media_type {
         MediaType::Video => {
             thumbnail::get_video_thumbnail_args(&request.input_path.to_string_lossy(), &request.id)
@@ -412,7 +1289,7 @@ async fn generate_single_thumbnail(request: ThumbnailRequest) -> Result<Thumbnai
 
     let sidecar_path = get_ffmpeg_path()?;
 
-    let mut cmd = Command::new(&sidecar_path);
+    let mut cmd = Command::new(sidecar_path);
     cmd.args(&args);
 
     #[cfg(windows)]
@@ -421,7 +1298,9 @@ async fn generate_single_thumbnail(request: ThumbnailRequest) -> Result<Thumbnai
     let output = cmd
         .output()
         .await
-        .map_err(|e| format!("Failed to generate thumbnail: {}", e))?;
+        .map_err(|e| ConversionError::ThumbnailError {
+            message: format!("Failed to generate thumbnail: {}", e),
+        })?;
 
     if output.status.success() && output_path.exists() {
         Ok(ThumbnailResult {
@@ -446,7 +1325,7 @@ const MAX_CONCURRENT_THUMBNAILS: usize = 3;
 pub async fn generate_thumbnails_batch(
     _app: AppHandle,
     requests: Vec<ThumbnailRequest>,
-) -> Result<Vec<ThumbnailResult>, String> {
+) -> Result<Vec<ThumbnailResult>, ConversionError> {
     use futures::stream::{self, StreamExt};
     use std::sync::Arc;
     use tokio::sync::Semaphore;
@@ -465,7 +1344,7 @@ pub async fn generate_thumbnails_batch(
                         id: req_id,
                         thumbnail_path: None,
                         success: false,
-                        error_message: Some(e),
+                        error_message: Some(e.to_string()),
                     })
             }
         })
@@ -477,36 +1356,43 @@ pub async fn generate_thumbnails_batch(
 }
 
 #[tauri::command]
-pub async fn cancel_conversion(state: State<'_, AppState>, id: String) -> Result<(), String> {
+pub async fn cancel_conversion(
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<(), ConversionError> {
     state.kill_process(&id).await?;
     Ok(())
 }
 
 #[tauri::command]
-pub async fn delete_thumbnails(file_ids: Vec<String>) -> Result<Vec<String>, String> {
+pub async fn delete_thumbnails(file_ids: Vec<String>) -> Result<Vec<String>, ConversionError> {
     let failed = thumbnail::delete_thumbnails(&file_ids).await;
     Ok(failed)
 }
 
 #[tauri::command]
-pub async fn cleanup_all_temp_thumbnails() -> Result<(), String> {
+pub async fn cleanup_all_temp_thumbnails() -> Result<(), ConversionError> {
     thumbnail::cleanup_all_temp_thumbnails().await;
     Ok(())
 }
 
 #[tauri::command]
-pub async fn get_file_info(path: String) -> Result<FileInfo, String> {
+pub async fn get_file_info(path: String) -> Result<FileInfo, ConversionError> {
     get_single_file_info(&path).await
 }
 
-async fn get_single_file_info(path_str: &str) -> Result<FileInfo, String> {
+async fn get_single_file_info(path_str: &str) -> Result<FileInfo, ConversionError> {
     let path = Path::new(path_str);
 
     if !path.exists() {
-        return Err("File not found".to_string());
+        return Err(ConversionError::FileNotFound {
+            path: path_str.to_string(),
+        });
     }
 
-    let metadata = tokio::fs::metadata(path).await.map_err(|e| e.to_string())?;
+    let metadata = tokio::fs::metadata(path)
+        .await
+        .map_err(|e| ConversionError::from_io(e, path))?;
     let size = metadata.len();
 
     let extension = path
@@ -514,8 +1400,13 @@ async fn get_single_file_info(path_str: &str) -> Result<FileInfo, String> {
         .map(|s| s.to_string_lossy().to_lowercase())
         .unwrap_or_default();
 
-    let media_type =
-        MediaType::from_extension(&extension).ok_or_else(|| "Unsupported format".to_string())?;
+    let media_type = MediaType::from_extension(&extension).ok_or_else(|| {
+        ConversionError::UnsupportedFormat {
+            input: extension.clone(),
+            output: String::new(),
+            reason: "Unsupported format".to_string(),
+        }
+    })?;
 
     Ok(FileInfo {
         path: path.to_string_lossy().to_string(),
@@ -529,7 +1420,9 @@ async fn get_single_file_info(path_str: &str) -> Result<FileInfo, String> {
 }
 
 #[tauri::command]
-pub async fn get_files_info_batch(paths: Vec<String>) -> Result<Vec<FileInfoResult>, String> {
+pub async fn get_files_info_batch(
+    paths: Vec<String>,
+) -> Result<Vec<FileInfoResult>, ConversionError> {
     use futures::stream::{self, StreamExt};
 
     let results: Vec<FileInfoResult> = stream::iter(paths)
@@ -543,7 +1436,7 @@ pub async fn get_files_info_batch(paths: Vec<String>) -> Result<Vec<FileInfoResu
                 Err(e) => FileInfoResult {
                     path,
                     info: None,
-                    error: Some(e),
+                    error: Some(e.to_string()),
                 },
             }
         })
@@ -555,6 +1448,223 @@ pub async fn get_files_info_batch(paths: Vec<String>) -> Result<Vec<FileInfoResu
 }
 
 #[tauri::command]
-pub async fn check_file_exists(path: String) -> bool {
-    tokio::fs::try_exists(path).await.unwrap_or(false)
+pub async fn scan_directory(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    request: ScanRequest,
+) -> Result<ScanSummary, ConversionError> {
+    let filter = scanner::ScanFilter::from_request(&request)?;
+    let roots: Vec<PathBuf> = request.paths.iter().map(PathBuf::from).collect();
+    for (root, path) in roots.iter().zip(&request.paths) {
+        if !tokio::fs::try_exists(root).await.unwrap_or(false) {
+            return Err(ConversionError::FileNotFound { path: path.clone() });
+        }
+    }
+
+    let id = request.id.clone();
+    let cancel = state.begin_scan(&id).await;
+    let chunk_size = scanner::chunk_size(request.chunk_size);
+    let max_depth = request.max_depth;
+    let chunk_id = id.clone();
+
+    let outcome = tauri::async_runtime::spawn_blocking(move || {
+        scanner::scan(
+            &roots,
+            max_depth,
+            &filter,
+            &cancel,
+            chunk_size,
+            |files, scanned_files| {
+                let _ = app.emit(
+                    "scan-progress",
+                    ScanChunk {
+                        id: chunk_id.clone(),
+                        files,
+                        scanned_files,
+                    },
+                );
+            },
+        )
+    })
+    .await;
+    state.end_scan(&id).await;
+
+    let outcome = outcome.map_err(|e| ConversionError::Unknown {
+        message: format!("Directory scan failed: {}", e),
+    })?;
+    Ok(ScanSummary {
+        id,
+        found: outcome.found,
+        scanned_files: outcome.scanned_files,
+        cancelled: outcome.cancelled,
+    })
+}
+
+#[tauri::command]
+pub async fn cancel_scan(state: State<'_, AppState>, id: String) -> Result<bool, ConversionError> {
+    Ok(state.cancel_scan(&id).await)
+}
+
+#[tauri::command]
+pub async fn get_file_metadata(id: String, path: String) -> Result<FileMetadata, ConversionError> {
+    let input_path = PathBuf::from(&path);
+    if !tokio::fs::try_exists(&input_path).await.unwrap_or(false) {
+        return Err(ConversionError::FileNotFound { path });
+    }
+
+    let (_, log) = run_ffmpeg_capture(&ffmpeg::build_probe_args(&input_path)).await?;
+    let tags = metadata::parse_media_tags(&log);
+
+    let cover_art_path = match tags.streams.iter().find(|s| s.attached_pic) {
+        Some(stream) => extract_cover_art(&id, &input_path, stream.index).await,
+        None => None,
+    };
+
+    let is_image = input_path
+        .extension()
+        .and_then(|e| MediaType::from_extension(&e.to_string_lossy()))
+        == Some(MediaType::Image);
+    let exif = if is_image {
+        tokio::fs::read(&input_path)
+            .await
+            .ok()
+            .and_then(|data| exif::read_exif(&data))
+    } else {
+        None
+    };
+
+    Ok(FileMetadata {
+        path,
+        format_tags: tags.format,
+        streams: tags.streams,
+        chapters: tags.chapters,
+        cover_art_path,
+        exif,
+    })
+}
+
+async fn extract_cover_art(id: &str, input_path: &Path, stream_index: usize) -> Option<PathBuf> {
+    let output_path = thumbnail::get_thumbnail_path(&format!("{}_cover", id));
+    let args = ffmpeg::build_attached_pic_args(input_path, stream_index, &output_path);
+    match run_ffmpeg_capture(&args).await {
+        Ok((Some(0), _)) if output_path.exists() => Some(output_path),
+        _ => None,
+    }
+}
+
+#[tauri::command]
+pub async fn export_chapters(input_path: String, path: String) -> Result<usize, ConversionError> {
+    let source = PathBuf::from(&input_path);
+    if !tokio::fs::try_exists(&source).await.unwrap_or(false) {
+        return Err(ConversionError::FileNotFound { path: input_path });
+    }
+
+    let (_, log) = run_ffmpeg_capture(&ffmpeg::build_probe_args(&source)).await?;
+    let found = metadata::parse_media_tags(&log).chapters;
+    chapters::write_chapter_file(Path::new(&path), &found).await?;
+    Ok(found.len())
+}
+
+#[tauri::command]
+pub async fn get_job_log(app: AppHandle, id: String) -> Result<String, ConversionError> {
+    job_log::read_job_log(&app, &id).await
+}
+
+#[tauri::command]
+pub async fn cleanup_job_logs(
+    app: AppHandle,
+    max_age_days: Option<u64>,
+    max_total_bytes: Option<u64>,
+) -> Result<usize, ConversionError> {
+    let max_age = max_age_days
+        .map(|days| std::time::Duration::from_secs(days.saturating_mul(24 * 60 * 60)))
+        .unwrap_or(job_log::DEFAULT_MAX_LOG_AGE);
+    job_log::cleanup_job_logs(
+        &app,
+        max_age,
+        max_total_bytes.unwrap_or(job_log::DEFAULT_MAX_LOG_TOTAL_BYTES),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn list_presets(app: AppHandle) -> Result<Vec<ConversionPreset>, ConversionError> {
+    presets::list_presets(&app).await
+}
+
+#[tauri::command]
+pub async fn create_preset(
+    app: AppHandle,
+    name: String,
+    settings: PresetSettings,
+) -> Result<ConversionPreset, ConversionError> {
+    presets::create_preset(&app, name, settings).await
+}
+
+#[tauri::command]
+pub async fn update_preset(
+    app: AppHandle,
+    id: String,
+    name: String,
+    settings: PresetSettings,
+) -> Result<ConversionPreset, ConversionError> {
+    presets::update_preset(&app, &id, name, settings).await
+}
+
+#[tauri::command]
+pub async fn delete_preset(app: AppHandle, id: String) -> Result<(), ConversionError> {
+    presets::delete_preset(&app, &id).await
+}
+
+#[tauri::command]
+pub async fn export_presets(
+    app: AppHandle,
+    ids: Vec<String>,
+    path: String,
+) -> Result<usize, ConversionError> {
+    presets::export_presets(&app, &ids, Path::new(&path)).await
+}
+
+#[tauri::command]
+pub async fn import_presets(
+    app: AppHandle,
+    path: String,
+    replace_existing: bool,
+) -> Result<PresetImportReport, ConversionError> {
+    presets::import_presets(&app, Path::new(&path), replace_existing).await
+}
+
+#[tauri::command]
+pub async fn list_watch_folders(app: AppHandle) -> Result<Vec<WatchFolder>, ConversionError> {
+    watcher::list_watch_folders(&app).await
+}
+
+#[tauri::command]
+pub async fn create_watch_folder(
+    app: AppHandle,
+    settings: WatchFolderSettings,
+) -> Result<WatchFolder, ConversionError> {
+    watcher::create_watch_folder(&app, settings).await
+}
+
+#[tauri::command]
+pub async fn update_watch_folder(
+    app: AppHandle,
+    id: String,
+    settings: WatchFolderSettings,
+) -> Result<WatchFolder, ConversionError> {
+    watcher::update_watch_folder(&app, &id, settings).await
+}
+
+#[tauri::command]
+pub async fn delete_watch_folder(app: AppHandle, id: String) -> Result<(), ConversionError> {
+    watcher::delete_watch_folder(&app, &id).await
+}
+
+#[tauri::command]
+pub async fn check_file_exists(path: String) -> Result<bool, ConversionError> {
+    let path = PathBuf::from(path);
+    tokio::fs::try_exists(&path)
+        .await
+        .map_err(|e| ConversionError::from_io(e, &path))
 }
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "details")]
@@ -28,6 +29,77 @@ pub enum ConversionError {
         id: String,
     },
 
+    FfmpegNotFound {
+        checked: Vec<String>,
+    },
+
+    DiskFull {
+        path: String,
+        hint: String,
+        log: String,
+    },
+
+    PermissionDenied {
+        path: String,
+        hint: String,
+        log: String,
+    },
+
+    MissingFile {
+        path: String,
+        hint: String,
+        log: String,
+    },
+
+    MissingDecoder {
+        codec: String,
+        hint: String,
+        log: String,
+    },
+
+    UnknownOption {
+        option: String,
+        hint: String,
+        log: String,
+    },
+
+    NoSuitableStream {
+        path: String,
+        hint: String,
+        log: String,
+    },
+
+    UnknownEncoder {
+        encoder: String,
+        hint: String,
+        log: String,
+    },
+
+    InvalidDimensions {
+        hint: String,
+        log: String,
+    },
+
+    CorruptInput {
+        path: String,
+        hint: String,
+        log: String,
+    },
+
+    UnsupportedPixelFormat {
+        hint: String,
+        log: String,
+    },
+
+    CodecContainerMismatch {
+        hint: String,
+        log: String,
+    },
+
+    TooManyDuplicates {
+        path: String,
+    },
+
     IoError {
         message: String,
     },
@@ -36,6 +108,19 @@ pub enum ConversionError {
         message: String,
     },
 
+    PresetNotFound {
+        id: String,
+    },
+
+    WatchFolderNotFound {
+        id: String,
+    },
+
+    VerificationFailed {
+        path: String,
+        failures: Vec<String>,
+    },
+
     ThumbnailError {
         message: String,
     },
@@ -75,18 +160,79 @@ impl fmt::Display for ConversionError {
                     "{} error (code: {:?}): {}",
                     tool,
                     exit_code,
-                    stderr.lines().next().unwrap_or("Unknown error")
+                    stderr
+                        .lines()
+                        .rev()
+                        .find(|l| !l.trim().is_empty())
+                        .unwrap_or("Unknown error")
                 )
             }
             ConversionError::Cancelled { id } => {
                 write!(f, "Process cancelled: {}", id)
             }
+            ConversionError::FfmpegNotFound { checked } => {
+                write!(f, "Could not find ffmpeg binary. Checked: {:?}", checked)
+            }
+            ConversionError::DiskFull { path, hint, .. } => {
+                write!(f, "Disk full while writing: {} ({})", path, hint)
+            }
+            ConversionError::PermissionDenied { path, hint, .. } => {
+                write!(f, "Permission denied: {} ({})", path, hint)
+            }
+            ConversionError::MissingFile { path, hint, .. } => {
+                write!(f, "No such file or directory: {} ({})", path, hint)
+            }
+            ConversionError::MissingDecoder { codec, hint, .. } => {
+                if codec.is_empty() {
+                    write!(f, "Missing decoder: {}", hint)
+                } else {
+                    write!(f, "Missing decoder: {} ({})", codec, hint)
+                }
+            }
+            ConversionError::UnknownOption { option, hint, .. } => {
+                write!(f, "Unknown option: {} ({})", option, hint)
+            }
+            ConversionError::NoSuitableStream { path, hint, .. } => {
+                write!(f, "Input file has no suitable stream: {} ({})", path, hint)
+            }
+            ConversionError::UnknownEncoder { encoder, hint, .. } => {
+                write!(f, "Unknown encoder: {} ({})", encoder, hint)
+            }
+            ConversionError::InvalidDimensions { hint, .. } => {
+                write!(f, "Invalid dimensions: {}", hint)
+            }
+            ConversionError::CorruptInput { path, hint, .. } => {
+                write!(f, "Corrupt input: {} ({})", path, hint)
+            }
+            ConversionError::UnsupportedPixelFormat { hint, .. } => {
+                write!(f, "Unsupported pixel format: {}", hint)
+            }
+            ConversionError::CodecContainerMismatch { hint, .. } => {
+                write!(f, "Codec not supported by container: {}", hint)
+            }
+            ConversionError::TooManyDuplicates { path } => {
+                write!(f, "Too many duplicate files: {}", path)
+            }
             ConversionError::IoError { message } => {
                 write!(f, "IO error: {}", message)
             }
             ConversionError::InvalidConfig { message } => {
                 write!(f, "Invalid configuration: {}", message)
             }
+            ConversionError::PresetNotFound { id } => {
+                write!(f, "Preset not found: {}", id)
+            }
+            ConversionError::WatchFolderNotFound { id } => {
+                write!(f, "Watch folder not found: {}", id)
+            }
+            ConversionError::VerificationFailed { path, failures } => {
+                write!(
+                    f,
+                    "Output verification failed for {}: {}",
+                    path,
+                    failures.join("; ")
+                )
+            }
             ConversionError::ThumbnailError { message } => {
                 write!(f, "Thumbnail error: {}", message)
             }
@@ -107,8 +253,29 @@ impl From<std::io::Error> for ConversionError {
     }
 }
 
-impl From<ConversionError> for String {
-    fn from(err: ConversionError) -> Self {
-        err.to_string()
+pub const DISK_FULL_HINT: &str =
+    "Free up space on the output drive or choose another output directory.";
+pub const PERMISSION_DENIED_HINT: &str =
+    "Check that the output directory is writable and the file is not open elsewhere.";
+
+impl ConversionError {
+    pub fn from_io(err: std::io::Error, path: &Path) -> Self {
+        let path = path.to_string_lossy().to_string();
+        match err.kind() {
+            std::io::ErrorKind::PermissionDenied => ConversionError::PermissionDenied {
+                path,
+                hint: PERMISSION_DENIED_HINT.to_string(),
+                log: String::new(),
+            },
+            std::io::ErrorKind::StorageFull => ConversionError::DiskFull {
+                path,
+                hint: DISK_FULL_HINT.to_string(),
+                log: String::new(),
+            },
+            std::io::ErrorKind::NotFound => ConversionError::FileNotFound { path },
+            _ => ConversionError::IoError {
+                message: format!("{}: {}", path, err),
+            },
+        }
     }
 }
@@ -3,6 +3,7 @@ mod error;
 mod modules;
 mod state;
 mod types;
+use modules::{job_log, temp_files, watcher};
 use state::AppState;
 use tauri::Manager;
 
@@ -20,7 +21,20 @@ pub fn run() {
             }
         }))
         .manage(AppState::new())
-        .setup(|_app| Ok(()))
+        .setup(|app| {
+            temp_files::sweep(app.handle());
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let _ = job_log::cleanup_job_logs(
+                    &handle,
+                    job_log::DEFAULT_MAX_LOG_AGE,
+                    job_log::DEFAULT_MAX_LOG_TOTAL_BYTES,
+                )
+                .await;
+            });
+            watcher::start(app.handle().clone());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             commands::convert_file,
             commands::generate_thumbnail,
@@ -30,7 +44,24 @@ pub fn run() {
             commands::cleanup_all_temp_thumbnails,
             commands::get_file_info,
             commands::get_files_info_batch,
+            commands::scan_directory,
+            commands::cancel_scan,
+            commands::get_file_metadata,
+            commands::export_chapters,
             commands::check_file_exists,
+            commands::get_job_log,
+            commands::cleanup_job_logs,
+            commands::preview_command,
+            commands::list_presets,
+            commands::create_preset,
+            commands::update_preset,
+            commands::delete_preset,
+            commands::export_presets,
+            commands::import_presets,
+            commands::list_watch_folders,
+            commands::create_watch_folder,
+            commands::update_watch_folder,
+            commands::delete_watch_folder,
         ])
         .build(tauri::generate_context!())
         .expect("Code 101: Failed to initialize Tauri application runtime");
@@ -0,0 +1,175 @@
+use crate::error::ConversionError;
+use crate::types::Chapter;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+const CHAPTERS_PREFIX: &str = "aether_chapters_";
+
+pub fn ffmetadata_path(file_id: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("{}{}.txt", CHAPTERS_PREFIX, file_id))
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|e| e.to_string_lossy().eq_ignore_ascii_case("json"))
+}
+
+/// Reads a chapter file. JSON files hold a list of chapters; anything else is
+/// read as text with one `HH:MM:SS[.mmm] Title` line per chapter.
+///
+/// Text chapters end where the next one starts, and the last one ends at
+/// `duration_secs` when it is known.
+pub async fn read_chapter_file(
+    path: &Path,
+    duration_secs: Option<f64>,
+) -> Result<Vec<Chapter>, ConversionError> {
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| ConversionError::from_io(e, path))?;
+
+    let mut chapters = if is_json(path) {
+        serde_json::from_str::<Vec<Chapter>>(&content).map_err(|e| invalid(format!("{}", e)))?
+    } else {
+        parse_text(&content, duration_secs)?
+    };
+
+    chapters.sort_by(|a, b| a.start_secs.total_cmp(&b.start_secs));
+    for chapter in &chapters {
+        if !chapter.start_secs.is_finite()
+            || chapter.start_secs < 0.0
+            || chapter.end_secs < chapter.start_secs
+        {
+            return Err(invalid(format!(
+                "chapter at {:.3}s has an invalid range",
+                chapter.start_secs
+            )));
+        }
+    }
+    Ok(chapters)
+}
+
+pub async fn write_chapter_file(path: &Path, chapters: &[Chapter]) -> Result<(), ConversionError> {
+    let content = if is_json(path) {
+        serde_json::to_string_pretty(chapters).map_err(|e| ConversionError::Unknown {
+            message: format!("Failed to serialize chapters: {}", e),
+        })?
+    } else {
+        chapters
+            .iter()
+            .map(|c| {
+                format!(
+                    "{} {}\n",
+                    format_timestamp(c.start_secs),
+                    c.title.as_deref().unwrap_or_default()
+                )
+            })
+            .collect()
+    };
+
+    tokio::fs::write(path, content)
+        .await
+        .map_err(|e| ConversionError::from_io(e, path))
+}
+
+fn parse_text(content: &str, duration_secs: Option<f64>) -> Result<Vec<Chapter>, ConversionError> {
+    let mut starts: Vec<(f64, String)> = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (timestamp, title) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let start = parse_timestamp(timestamp).ok_or_else(|| {
+            invalid(format!(
+                "line {}: '{}' is not a timestamp",
+                number + 1,
+                timestamp
+            ))
+        })?;
+        starts.push((start, title.trim().to_string()));
+    }
+    starts.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let ends: Vec<f64> = starts
+        .iter()
+        .skip(1)
+        .map(|(start, _)| *start)
+        .chain(std::iter::once(duration_secs.unwrap_or_else(|| {
+            starts.last().map(|s| s.0).unwrap_or_default()
+        })))
+        .collect();
+
+    Ok(starts
+        .into_iter()
+        .zip(ends)
+        .map(|((start, title), end)| Chapter {
+            start_secs: start,
+            end_secs: end.max(start),
+            title: (!title.is_empty()).then_some(title),
+        })
+        .collect())
+}
+
+// Accepts `SS`, `MM:SS` and `HH:MM:SS`, each with optional fractions.
+fn parse_timestamp(value: &str) -> Option<f64> {
+    let mut total = 0.0;
+    let parts: Vec<&str> = value.split(':').collect();
+    if parts.len() > 3 {
+        return None;
+    }
+    for part in parts {
+        let n: f64 = part.parse().ok()?;
+        if !n.is_finite() || n < 0.0 {
+            return None;
+        }
+        total = total * 60.0 + n;
+    }
+    Some(total)
+}
+
+fn format_timestamp(secs: f64) -> String {
+    let millis = (secs * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+/// Renders chapters in ffmpeg's FFMETADATA format so they can be mapped into
+/// the output with `-map_chapters`.
+pub fn to_ffmetadata(chapters: &[Chapter]) -> String {
+    let mut out = String::from(";FFMETADATA1\n");
+    for chapter in chapters {
+        let _ = writeln!(out, "[CHAPTER]\nTIMEBASE=1/1000");
+        let _ = writeln!(
+            out,
+            "START={}",
+            (chapter.start_secs * 1000.0).round() as u64
+        );
+        let _ = writeln!(out, "END={}", (chapter.end_secs * 1000.0).round() as u64);
+        if let Some(title) = &chapter.title {
+            let _ = writeln!(out, "title={}", escape_ffmetadata(title));
+        }
+    }
+    out
+}
+
+fn escape_ffmetadata(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '=' | ';' | '#' | '\\' | '\n') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn invalid(message: String) -> ConversionError {
+    ConversionError::InvalidConfig {
+        message: format!("chapters: {}", message),
+    }
+}
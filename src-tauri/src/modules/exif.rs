@@ -0,0 +1,451 @@
+use crate::types::{ExifData, GpsCoordinates};
+
+const TAG_MAKE: u16 = 0x010F;
+const TAG_MODEL: u16 = 0x0110;
+const TAG_ORIENTATION: u16 = 0x0112;
+const TAG_DATE_TIME: u16 = 0x0132;
+const TAG_EXIF_IFD: u16 = 0x8769;
+const TAG_GPS_IFD: u16 = 0x8825;
+const TAG_EXPOSURE_TIME: u16 = 0x829A;
+const TAG_F_NUMBER: u16 = 0x829D;
+const TAG_ISO: u16 = 0x8827;
+const TAG_DATE_TIME_ORIGINAL: u16 = 0x9003;
+const TAG_FOCAL_LENGTH: u16 = 0x920A;
+const TAG_LENS_MODEL: u16 = 0xA434;
+const TAG_GPS_LATITUDE_REF: u16 = 0x0001;
+const TAG_GPS_LATITUDE: u16 = 0x0002;
+const TAG_GPS_LONGITUDE_REF: u16 = 0x0003;
+const TAG_GPS_LONGITUDE: u16 = 0x0004;
+const TAG_GPS_ALTITUDE_REF: u16 = 0x0005;
+const TAG_GPS_ALTITUDE: u16 = 0x0006;
+
+const EXIF_HEADER: &[u8] = b"Exif\0\0";
+
+/// Reads the commonly displayed EXIF fields from JPEG, PNG, WebP or TIFF data.
+pub fn read_exif(data: &[u8]) -> Option<ExifData> {
+    let tiff = Tiff::new(find_tiff(data)?)?;
+    let ifd0 = tiff.u32(4)? as usize;
+    // A cut-off first directory means the data is damaged, not just sparse.
+    let entries = tiff.u16(ifd0)? as usize;
+    tiff.data.get(ifd0..ifd0 + 2 + entries * 12)?;
+
+    let mut exif = ExifData {
+        camera_make: tiff.ascii(ifd0, TAG_MAKE),
+        camera_model: tiff.ascii(ifd0, TAG_MODEL),
+        orientation: tiff.short(ifd0, TAG_ORIENTATION),
+        date_taken: tiff.ascii(ifd0, TAG_DATE_TIME),
+        ..Default::default()
+    };
+
+    if let Some(exif_ifd) = tiff.long(ifd0, TAG_EXIF_IFD) {
+        let exif_ifd = exif_ifd as usize;
+        exif.lens_model = tiff.ascii(exif_ifd, TAG_LENS_MODEL);
+        exif.exposure_time = tiff
+            .rationals(exif_ifd, TAG_EXPOSURE_TIME)
+            .and_then(|r| r.first().copied())
+            .map(format_exposure);
+        exif.f_number = tiff.rational(exif_ifd, TAG_F_NUMBER);
+        exif.focal_length_mm = tiff.rational(exif_ifd, TAG_FOCAL_LENGTH);
+        exif.iso = tiff.short(exif_ifd, TAG_ISO).map(u32::from);
+        if let Some(original) = tiff.ascii(exif_ifd, TAG_DATE_TIME_ORIGINAL) {
+            exif.date_taken = Some(original);
+        }
+    }
+
+    if let Some(gps_ifd) = tiff.long(ifd0, TAG_GPS_IFD) {
+        exif.gps = read_gps(&tiff, gps_ifd as usize);
+    }
+
+    Some(exif)
+}
+
+fn read_gps(tiff: &Tiff, ifd: usize) -> Option<GpsCoordinates> {
+    let latitude = to_degrees(&tiff.rationals(ifd, TAG_GPS_LATITUDE)?)?;
+    let longitude = to_degrees(&tiff.rationals(ifd, TAG_GPS_LONGITUDE)?)?;
+    let lat_sign = if tiff.ascii(ifd, TAG_GPS_LATITUDE_REF).as_deref() == Some("S") {
+        -1.0
+    } else {
+        1.0
+    };
+    let lon_sign = if tiff.ascii(ifd, TAG_GPS_LONGITUDE_REF).as_deref() == Some("W") {
+        -1.0
+    } else {
+        1.0
+    };
+    let altitude = tiff.rational(ifd, TAG_GPS_ALTITUDE).map(|alt| {
+        if tiff.byte(ifd, TAG_GPS_ALTITUDE_REF) == Some(1) {
+            -alt
+        } else {
+            alt
+        }
+    });
+
+    Some(GpsCoordinates {
+        latitude: latitude * lat_sign,
+        longitude: longitude * lon_sign,
+        altitude,
+    })
+}
+
+fn to_degrees(parts: &[(u32, u32)]) -> Option<f64> {
+    let value = |i: usize| {
+        parts
+            .get(i)
+            .filter(|(_, den)| *den != 0)
+            .map(|(num, den)| *num as f64 / *den as f64)
+    };
+    Some(value(0)? + value(1).unwrap_or(0.0) / 60.0 + value(2).unwrap_or(0.0) / 3600.0)
+}
+
+fn format_exposure((num, den): (u32, u32)) -> String {
+    if num == 0 || den == 0 {
+        return "0".to_string();
+    }
+    if num >= den {
+        format!("{}", num as f64 / den as f64)
+    } else {
+        format!("1/{}", (den as f64 / num as f64).round())
+    }
+}
+
+// Locates the TIFF structure that holds EXIF data inside each container.
+fn find_tiff(data: &[u8]) -> Option<&[u8]> {
+    if data.starts_with(b"II*\0") || data.starts_with(b"MM\0*") {
+        return Some(data);
+    }
+    if data.starts_with(&[0xFF, 0xD8]) {
+        return find_tiff_in_jpeg(data);
+    }
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return find_png_chunk(data, b"eXIf");
+    }
+    if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        let chunk = find_webp_chunk(data, b"EXIF")?;
+        return Some(chunk.strip_prefix(EXIF_HEADER).unwrap_or(chunk));
+    }
+    None
+}
+
+fn find_tiff_in_jpeg(data: &[u8]) -> Option<&[u8]> {
+    let mut pos = 2;
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            return None;
+        }
+        let marker = data[pos + 1];
+        // Start of scan: no more metadata segments follow.
+        if marker == 0xDA {
+            return None;
+        }
+        let len = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        let segment = data.get(pos + 4..pos + 2 + len)?;
+        if marker == 0xE1 {
+            if let Some(tiff) = segment.strip_prefix(EXIF_HEADER) {
+                return Some(tiff);
+            }
+        }
+        pos += 2 + len;
+    }
+    None
+}
+
+fn find_png_chunk<'a>(data: &'a [u8], kind: &[u8; 4]) -> Option<&'a [u8]> {
+    let mut pos = 8;
+    while pos + 8 <= data.len() {
+        let len = u32::from_be_bytes(data[pos..pos + 4].try_into().ok()?) as usize;
+        let chunk_type = &data[pos + 4..pos + 8];
+        let body = data.get(pos + 8..pos + 8 + len)?;
+        if chunk_type == kind {
+            return Some(body);
+        }
+        // Length, type, body and CRC.
+        pos += 12 + len;
+    }
+    None
+}
+
+fn find_webp_chunk<'a>(data: &'a [u8], kind: &[u8; 4]) -> Option<&'a [u8]> {
+    let mut pos = 12;
+    while pos + 8 <= data.len() {
+        let len = u32::from_le_bytes(data[pos + 4..pos + 8].try_into().ok()?) as usize;
+        let body = data.get(pos + 8..pos + 8 + len)?;
+        if &data[pos..pos + 4] == kind {
+            return Some(body);
+        }
+        // Chunks are padded to an even length.
+        pos += 8 + len + (len & 1);
+    }
+    None
+}
+
+struct Tiff<'a> {
+    data: &'a [u8],
+    little_endian: bool,
+}
+
+impl<'a> Tiff<'a> {
+    fn new(data: &'a [u8]) -> Option<Self> {
+        let little_endian = match data.get(0..2)? {
+            b"II" => true,
+            b"MM" => false,
+            _ => return None,
+        };
+        Some(Self {
+            data,
+            little_endian,
+        })
+    }
+
+    fn u16(&self, offset: usize) -> Option<u16> {
+        let bytes: [u8; 2] = self.data.get(offset..offset + 2)?.try_into().ok()?;
+        Some(if self.little_endian {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        })
+    }
+
+    fn u32(&self, offset: usize) -> Option<u32> {
+        let bytes: [u8; 4] = self.data.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if self.little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+
+    // Returns the field type, value count and the bytes holding the values.
+    fn entry(&self, ifd: usize, tag: u16) -> Option<(u16, usize, &'a [u8])> {
+        let count = self.u16(ifd)? as usize;
+        for i in 0..count {
+            let entry = ifd + 2 + i * 12;
+            if self.u16(entry)? != tag {
+                continue;
+            }
+            let field_type = self.u16(entry + 2)?;
+            let value_count = self.u32(entry + 4)? as usize;
+            let unit = match field_type {
+                1 | 2 | 6 | 7 => 1,
+                3 | 8 => 2,
+                4 | 9 | 11 | 13 => 4,
+                5 | 10 | 12 => 8,
+                _ => return None,
+            };
+            let size = unit * value_count;
+            let start = if size <= 4 {
+                entry + 8
+            } else {
+                self.u32(entry + 8)? as usize
+            };
+            return Some((field_type, value_count, self.data.get(start..start + size)?));
+        }
+        None
+    }
+
+    fn ascii(&self, ifd: usize, tag: u16) -> Option<String> {
+        let (_, _, bytes) = self.entry(ifd, tag)?;
+        let text = String::from_utf8_lossy(bytes);
+        let text = text.trim_end_matches('\0').trim();
+        (!text.is_empty()).then(|| text.to_string())
+    }
+
+    fn byte(&self, ifd: usize, tag: u16) -> Option<u8> {
+        self.entry(ifd, tag)?.2.first().copied()
+    }
+
+    fn short(&self, ifd: usize, tag: u16) -> Option<u16> {
+        let (field_type, _, bytes) = self.entry(ifd, tag)?;
+        match field_type {
+            3 => Tiff {
+                data: bytes,
+                ..*self
+            }
+            .u16(0),
+            4 => Tiff {
+                data: bytes,
+                ..*self
+            }
+            .u32(0)
+            .and_then(|v| u16::try_from(v).ok()),
+            _ => None,
+        }
+    }
+
+    fn long(&self, ifd: usize, tag: u16) -> Option<u32> {
+        let (field_type, _, bytes) = self.entry(ifd, tag)?;
+        let view = Tiff {
+            data: bytes,
+            ..*self
+        };
+        match field_type {
+            3 => view.u16(0).map(u32::from),
+            4 | 13 => view.u32(0),
+            _ => None,
+        }
+    }
+
+    fn rationals(&self, ifd: usize, tag: u16) -> Option<Vec<(u32, u32)>> {
+        let (field_type, count, bytes) = self.entry(ifd, tag)?;
+        if field_type != 5 {
+            return None;
+        }
+        let view = Tiff {
+            data: bytes,
+            ..*self
+        };
+        (0..count)
+            .map(|i| Some((view.u32(i * 8)?, view.u32(i * 8 + 4)?)))
+            .collect()
+    }
+
+    fn rational(&self, ifd: usize, tag: u16) -> Option<f64> {
+        let (num, den) = *self.rationals(ifd, tag)?.first()?;
+        (den != 0).then(|| num as f64 / den as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // IFD0 holds a Make string stored at `make_offset` and an inline
+    // Orientation of 6.
+    fn tiff(little_endian: bool, make_offset: u32) -> Vec<u8> {
+        let u16b = |v: u16| {
+            if little_endian {
+                v.to_le_bytes()
+            } else {
+                v.to_be_bytes()
+            }
+        };
+        let u32b = |v: u32| {
+            if little_endian {
+                v.to_le_bytes()
+            } else {
+                v.to_be_bytes()
+            }
+        };
+        let mut data = Vec::new();
+        data.extend_from_slice(if little_endian { b"II" } else { b"MM" });
+        data.extend_from_slice(&u16b(42));
+        data.extend_from_slice(&u32b(8));
+        data.extend_from_slice(&u16b(2));
+        data.extend_from_slice(&u16b(TAG_MAKE));
+        data.extend_from_slice(&u16b(2));
+        data.extend_from_slice(&u32b(6));
+        data.extend_from_slice(&u32b(make_offset));
+        data.extend_from_slice(&u16b(TAG_ORIENTATION));
+        data.extend_from_slice(&u16b(3));
+        data.extend_from_slice(&u32b(1));
+        data.extend_from_slice(&u16b(6));
+        data.extend_from_slice(&[0, 0]);
+        data.extend_from_slice(&u32b(0));
+        data.extend_from_slice(b"Canon\0");
+        data
+    }
+
+    const MAKE_OFFSET: u32 = 38;
+
+    fn jpeg(app1_len: u16, body: &[u8]) -> Vec<u8> {
+        let mut data = vec![0xFF, 0xD8, 0xFF, 0xE1];
+        data.extend_from_slice(&app1_len.to_be_bytes());
+        data.extend_from_slice(body);
+        data.extend_from_slice(&[0xFF, 0xDA]);
+        data
+    }
+
+    fn png(chunk_len: u32, body: &[u8]) -> Vec<u8> {
+        let mut data = b"\x89PNG\r\n\x1a\n".to_vec();
+        data.extend_from_slice(&chunk_len.to_be_bytes());
+        data.extend_from_slice(b"eXIf");
+        data.extend_from_slice(body);
+        data.extend_from_slice(&[0; 4]);
+        data
+    }
+
+    fn webp(body: &[u8]) -> Vec<u8> {
+        let mut data = b"RIFF".to_vec();
+        data.extend_from_slice(&(4 + 8 + body.len() as u32).to_le_bytes());
+        data.extend_from_slice(b"WEBPEXIF");
+        data.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        data.extend_from_slice(body);
+        data
+    }
+
+    fn exif_body(tiff: &[u8]) -> Vec<u8> {
+        [EXIF_HEADER, tiff].concat()
+    }
+
+    #[test]
+    fn reads_both_byte_orders() {
+        for little_endian in [true, false] {
+            let exif = read_exif(&tiff(little_endian, MAKE_OFFSET)).unwrap();
+            assert_eq!(exif.camera_make.as_deref(), Some("Canon"));
+            assert_eq!(exif.orientation, Some(6));
+        }
+    }
+
+    #[test]
+    fn truncated_ifd_is_rejected() {
+        for little_endian in [true, false] {
+            let data = tiff(little_endian, MAKE_OFFSET);
+            assert!(read_exif(&data[..20]).is_none());
+        }
+    }
+
+    #[test]
+    fn out_of_range_value_offset_drops_only_that_field() {
+        for offset in [MAKE_OFFSET + 1, 0xFFFF_FFFF] {
+            let exif = read_exif(&tiff(true, offset)).unwrap();
+            assert_eq!(exif.camera_make, None);
+            assert_eq!(exif.orientation, Some(6));
+        }
+    }
+
+    #[test]
+    fn jpeg_app1_segment() {
+        let body = exif_body(&tiff(false, MAKE_OFFSET));
+        let exif = read_exif(&jpeg(body.len() as u16 + 2, &body)).unwrap();
+        assert_eq!(exif.camera_make.as_deref(), Some("Canon"));
+
+        for len in [0, 1] {
+            assert!(read_exif(&jpeg(len, &body)).is_none());
+        }
+        assert!(read_exif(&jpeg(u16::MAX, &body)).is_none());
+    }
+
+    #[test]
+    fn png_exif_chunk() {
+        let body = tiff(true, MAKE_OFFSET);
+        let exif = read_exif(&png(body.len() as u32, &body)).unwrap();
+        assert_eq!(exif.camera_make.as_deref(), Some("Canon"));
+
+        assert!(read_exif(&png(u32::MAX, &body)).is_none());
+    }
+
+    #[test]
+    fn webp_exif_chunk_with_and_without_header() {
+        let tiff = tiff(false, MAKE_OFFSET);
+        for body in [exif_body(&tiff), tiff] {
+            let exif = read_exif(&webp(&body)).unwrap();
+            assert_eq!(exif.camera_make.as_deref(), Some("Canon"));
+        }
+    }
+
+    #[test]
+    fn truncated_input_never_panics() {
+        let tiff = tiff(true, MAKE_OFFSET);
+        let body = exif_body(&tiff);
+        let samples = [
+            tiff.clone(),
+            jpeg(body.len() as u16 + 2, &body),
+            png(tiff.len() as u32, &tiff),
+            webp(&body),
+        ];
+        for sample in &samples {
+            for end in 0..sample.len() {
+                let _ = read_exif(&sample[..end]);
+            }
+        }
+    }
+}
@@ -1,10 +1,12 @@
 use crate::types::{
-    AudioFormat, BackgroundColor, ConversionConfig, ImageFormat, OutputFormat, ResizeConfig,
-    ResizeMode, VideoFormat,
+    AudioFormat, BackgroundColor, ConversionConfig, ImageFormat, OutputFormat, QualitySetting,
+    ResizeConfig, ResizeMode, VideoFormat,
 };
 use std::ffi::OsString;
 use std::path::Path;
 
+pub const VIDEO_AUDIO_BITRATE_KBPS: u64 = 192;
+
 struct FFmpegCommandBuilder {
     args: Vec<OsString>,
 }
@@ -35,16 +37,27 @@ impl FFmpegCommandBuilder {
         if strip {
             self.args.push("-map_metadata".into());
             self.args.push("-1".into());
-            self.args.push("-map_chapters".into());
-            self.args.push("-1".into());
         }
         self
     }
 
-    fn strip_metadata_audio(mut self, strip: bool) -> Self {
-        if strip {
-            self.args.push("-map_metadata".into());
-            self.args.push("-1".into());
+    // Chapters come from the source unless they are stripped or imported
+    // from the input at `import_index`.
+    fn chapters(mut self, keep: bool, import_index: Option<usize>) -> Self {
+        let map = match import_index {
+            Some(index) => index.to_string(),
+            None if !keep => "-1".to_string(),
+            None => return self,
+        };
+        self.args.push("-map_chapters".into());
+        self.args.push(map.into());
+        self
+    }
+
+    fn metadata_tags(mut self, tags: &[(String, String)]) -> Self {
+        for (key, value) in tags {
+            self.args.push("-metadata".into());
+            self.args.push(format!("{}={}", key, value).into());
         }
         self
     }
@@ -61,6 +74,11 @@ impl FFmpegCommandBuilder {
         self
     }
 
+    fn hide_banner(mut self) -> Self {
+        self.args.push("-hide_banner".into());
+        self
+    }
+
     fn ignore_unknown(mut self) -> Self {
         self.args.push("-ignore_unknown".into());
         self
@@ -78,6 +96,11 @@ impl FFmpegCommandBuilder {
         self
     }
 
+    fn extra_args(mut self, args: &[String]) -> Self {
+        self.args.extend(args.iter().map(OsString::from));
+        self
+    }
+
     fn output(mut self, path: &Path) -> Self {
         self.args.push(path.as_os_str().to_owned());
         self
@@ -88,12 +111,32 @@ impl FFmpegCommandBuilder {
     }
 }
 
+pub fn build_args(config: &ConversionConfig) -> Vec<OsString> {
+    match &config.output_format {
+        OutputFormat::Video(_) => build_video_args(config),
+        OutputFormat::Audio(_) => build_audio_extract_args(config),
+        OutputFormat::Image(_) => build_image_args(config),
+    }
+}
+
 pub fn build_video_args(config: &ConversionConfig) -> Vec<OsString> {
     let mut builder = FFmpegCommandBuilder::new()
-        .input(&config.input_path)
+        .extra_args(&config.extra_input_args)
+        .input(&config.input_path);
+
+    if let Some(ref chapters) = config.chapters_file {
+        builder = builder.input(chapters);
+    }
+
+    builder = builder
         .progress()
-        .overwrite(config.conflict_mode == "overwrite")
+        .overwrite(config.overwrite)
         .strip_metadata(config.strip_metadata)
+        .chapters(
+            config.keep_chapters,
+            config.chapters_file.as_ref().map(|_| 1),
+        )
+        .metadata_tags(&config.metadata_tags)
         .mute(config.is_muted)
         .ignore_unknown()
         .arg("-map", "0:v:0");
@@ -107,7 +150,7 @@ pub fn build_video_args(config: &ConversionConfig) -> Vec<OsString> {
         builder = builder.filter_complex(filter);
     }
 
-    let audio_bitrate = "192k";
+    let audio_bitrate = format!("{}k", VIDEO_AUDIO_BITRATE_KBPS);
     match &config.output_format {
         OutputFormat::Video(VideoFormat::Webm) => {
             builder = builder.arg("-c:v", "libvpx-vp9");
@@ -115,9 +158,9 @@ pub fn build_video_args(config: &ConversionConfig) -> Vec<OsString> {
                 builder = builder.arg("-c:a", "libopus").arg("-b:a", &audio_bitrate);
             }
 
-            let vp9_crf = config.quality_value;
-
-            builder = builder.arg("-crf", &vp9_crf.to_string());
+            if let QualitySetting::Crf(vp9_crf) = config.quality {
+                builder = builder.arg("-crf", &vp9_crf.to_string());
+            }
 
             if let Some(user_max) = config.max_bitrate {
                 builder = builder
@@ -137,11 +180,23 @@ pub fn build_video_args(config: &ConversionConfig) -> Vec<OsString> {
         OutputFormat::Video(ref v @ (VideoFormat::Mp4 | VideoFormat::Mkv | VideoFormat::Mov)) => {
             builder = builder.arg("-c:v", "libx264");
             if !config.is_muted {
-                builder = builder.arg("-c:a", "aac").arg("-b:a", &audio_bitrate);
+                if matches!(config.quality, QualitySetting::Crf(0)) {
+                    // CRF 0 is lossless video, so the audio stays lossless too.
+                    let codec = if matches!(v, VideoFormat::Mkv) {
+                        "flac"
+                    } else {
+                        "alac"
+                    };
+                    builder = builder.arg("-c:a", codec);
+                } else {
+                    builder = builder.arg("-c:a", "aac").arg("-b:a", &audio_bitrate);
+                }
+            }
+            if let QualitySetting::Crf(crf) = config.quality {
+                builder = builder.arg("-crf", &crf.to_string());
             }
             builder = builder
-                .arg("-crf", &config.quality_value.to_string())
-                .arg("-preset", &config.video_preset)
+                .arg("-preset", config.video_preset.as_str())
                 .arg("-pix_fmt", "yuv420p");
             if matches!(v, VideoFormat::Mp4 | VideoFormat::Mov) {
                 builder = builder.arg("-movflags", "+faststart");
@@ -164,16 +219,47 @@ pub fn build_video_args(config: &ConversionConfig) -> Vec<OsString> {
         }
     }
 
-    builder.output(&config.output_path).build()
+    builder
+        .extra_args(&config.extra_output_args)
+        .output(&config.output_path)
+        .build()
 }
 
 pub fn build_audio_extract_args(config: &ConversionConfig) -> Vec<OsString> {
     let mut builder = FFmpegCommandBuilder::new()
-        .input(&config.input_path)
+        .extra_args(&config.extra_input_args)
+        .input(&config.input_path);
+
+    if let Some(ref cover) = config.cover_art_path {
+        builder = builder.input(cover);
+    }
+    if let Some(ref chapters) = config.chapters_file {
+        builder = builder.input(chapters);
+    }
+    let chapters_index = config
+        .chapters_file
+        .as_ref()
+        .map(|_| 1 + usize::from(config.cover_art_path.is_some()));
+
+    builder = builder
         .progress()
-        .overwrite(config.conflict_mode == "overwrite")
-        .strip_metadata_audio(config.strip_metadata)
-        .skip_video();
+        .overwrite(config.overwrite)
+        .strip_metadata(config.strip_metadata)
+        .chapters(config.keep_chapters, chapters_index)
+        .metadata_tags(&config.metadata_tags);
+
+    if config.cover_art_path.is_some() {
+        builder = builder
+            .arg("-map", "0:a:0")
+            .arg("-map", "1:v:0")
+            .arg("-c:v", "mjpeg")
+            .arg("-disposition:v:0", "attached_pic");
+        if matches!(config.output_format, OutputFormat::Audio(AudioFormat::Mp3)) {
+            builder = builder.arg("-id3v2_version", "3");
+        }
+    } else {
+        builder = builder.skip_video();
+    }
 
     if let OutputFormat::Audio(audio_format) = &config.output_format {
         builder = builder.arg(
@@ -183,31 +269,36 @@ pub fn build_audio_extract_args(config: &ConversionConfig) -> Vec<OsString> {
                 AudioFormat::Aac => "aac",
                 AudioFormat::M4a => "aac",
                 AudioFormat::Ogg => "libvorbis",
+                AudioFormat::Flac => "flac",
             },
         );
 
-        match audio_format {
-            AudioFormat::Mp3 => {
-                builder = builder.arg("-q:a", &config.quality_value.min(9).to_string());
+        match config.quality {
+            QualitySetting::AudioVbr(q) => {
+                builder = builder.arg("-q:a", &q.to_string());
             }
-            AudioFormat::Aac | AudioFormat::M4a => {
-                let final_bitrate = config.quality_value.min(320).max(128);
-                builder = builder.arg("-b:a", &format!("{}k", final_bitrate));
+            QualitySetting::AudioBitrateKbps(kbps) => {
+                builder = builder.arg("-b:a", &format!("{}k", kbps));
             }
-            AudioFormat::Ogg => {
-                let q = config.quality_value.max(1).min(8);
-                builder = builder.arg("-q:a", &q.to_string());
+            QualitySetting::Lossless => {
+                builder = builder.arg("-compression_level", "8");
             }
+            _ => {}
         }
     }
 
-    builder.output(&config.output_path).build()
+    builder
+        .extra_args(&config.extra_output_args)
+        .output(&config.output_path)
+        .build()
 }
 
 pub fn build_image_args(config: &ConversionConfig) -> Vec<OsString> {
     let mut builder = FFmpegCommandBuilder::new()
+        .extra_args(&config.extra_input_args)
         .input(&config.input_path)
-        .overwrite(config.conflict_mode == "overwrite");
+        .overwrite(config.overwrite)
+        .strip_metadata(config.strip_metadata);
 
     let mut filters: Vec<String> = Vec::new();
     if let Some(ref resize) = config.resize_config {
@@ -217,22 +308,25 @@ pub fn build_image_args(config: &ConversionConfig) -> Vec<OsString> {
         builder = builder.filter_complex(filters.join(","));
     }
 
-    match &config.output_format {
-        OutputFormat::Image(ImageFormat::Jpg) => {
-            builder = builder.arg("-q:v", &config.quality_value.max(2).min(31).to_string());
+    match (&config.output_format, config.quality) {
+        (OutputFormat::Image(ImageFormat::Jpg), QualitySetting::ImageQscale(q)) => {
+            builder = builder.arg("-q:v", &q.to_string());
         }
-        OutputFormat::Image(ImageFormat::Webp) => {
-            builder = builder.arg("-quality", &config.quality_value.to_string());
+        (OutputFormat::Image(ImageFormat::Webp), QualitySetting::WebpQuality(q)) => {
+            builder = builder.arg("-quality", &q.to_string());
             builder = builder
                 .arg("-preset", "photo")
                 .arg("-compression_level", "6");
         }
-        OutputFormat::Image(ImageFormat::Png) => {
+        (OutputFormat::Image(ImageFormat::Png), _) => {
             builder = builder.arg("-compression_level", "9");
         }
         _ => {}
     }
-    builder.output(&config.output_path).build()
+    builder
+        .extra_args(&config.extra_output_args)
+        .output(&config.output_path)
+        .build()
 }
 
 fn build_resize_filter(
@@ -299,6 +393,96 @@ pub fn build_image_thumbnail_args(input_path: &Path, output_path: &Path) -> Vec<
         .build()
 }
 
+pub fn build_cover_frame_args(
+    input_path: &Path,
+    time_secs: f64,
+    output_path: &Path,
+) -> Vec<OsString> {
+    FFmpegCommandBuilder::new()
+        .arg("-ss", &format!("{:.3}", time_secs))
+        .input(input_path)
+        .arg("-frames:v", "1")
+        .arg("-q:v", "2")
+        .overwrite(true)
+        .output(output_path)
+        .build()
+}
+
+// Stream copy into the source's own container, so only the metadata choices
+// of the request change the file.
+pub fn build_remux_args(
+    input_path: &Path,
+    output_path: &Path,
+    strip_metadata: bool,
+) -> Vec<OsString> {
+    FFmpegCommandBuilder::new()
+        .input(input_path)
+        .overwrite(true)
+        .strip_metadata(strip_metadata)
+        .arg("-map", "0")
+        .arg("-c", "copy")
+        .output(output_path)
+        .build()
+}
+
+pub fn build_attached_pic_args(
+    input_path: &Path,
+    stream_index: usize,
+    output_path: &Path,
+) -> Vec<OsString> {
+    FFmpegCommandBuilder::new()
+        .input(input_path)
+        .arg("-map", &format!("0:{}", stream_index))
+        .arg("-frames:v", "1")
+        .overwrite(true)
+        .output(output_path)
+        .build()
+}
+
+// No output is given, so ffmpeg prints the stream summary and exits non-zero.
+pub fn build_probe_args(input_path: &Path) -> Vec<OsString> {
+    FFmpegCommandBuilder::new()
+        .hide_banner()
+        .input(input_path)
+        .build()
+}
+
+pub fn build_decode_check_args(input_path: &Path) -> Vec<OsString> {
+    FFmpegCommandBuilder::new()
+        .hide_banner()
+        .arg("-v", "error")
+        .input(input_path)
+        .arg("-f", "null")
+        .output(Path::new("-"))
+        .build()
+}
+
+pub fn format_command_line(program: &Path, args: &[OsString]) -> String {
+    std::iter::once(program.as_os_str())
+        .chain(args.iter().map(|a| a.as_os_str()))
+        .map(|a| shell_quote(&a.to_string_lossy()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(windows)]
+fn shell_quote(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains(|c: char| c.is_whitespace() || "\"&|<>^%".contains(c)) {
+        return arg.to_string();
+    }
+    // cmd expands %VAR% even inside quotes, so each % is escaped outside them.
+    format!("\"{}\"", arg.replace('"', "\"\"").replace('%', "\"^%\""))
+}
+
+#[cfg(not(windows))]
+fn shell_quote(arg: &str) -> String {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "-_./:=+,@%".contains(c);
+    if !arg.is_empty() && arg.chars().all(is_safe) {
+        return arg.to_string();
+    }
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
 pub fn parse_progress(line: &str, duration_secs: Option<f64>) -> Option<u8> {
     if let Some(us_str) = line.strip_prefix("out_time_us=") {
         if let Ok(us) = us_str.trim().parse::<u64>() {
@@ -343,6 +527,21 @@ pub fn parse_progress(line: &str, duration_secs: Option<f64>) -> Option<u8> {
     None
 }
 
+pub fn is_progress_line(line: &str) -> bool {
+    if line.starts_with("frame=") || line.starts_with("size=") {
+        return true;
+    }
+    match line.split_once('=') {
+        Some((key, _)) => {
+            !key.is_empty()
+                && key
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        }
+        None => false,
+    }
+}
+
 pub fn parse_duration(line: &str) -> Option<f64> {
     if let Some(idx) = line.find("Duration: ") {
         let dur_str = &line[idx + 10..];
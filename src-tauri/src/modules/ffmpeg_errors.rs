@@ -0,0 +1,329 @@
+use crate::error::{ConversionError, DISK_FULL_HINT, PERMISSION_DENIED_HINT};
+use regex::Regex;
+use std::path::Path;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy)]
+enum FailureKind {
+    NoSuitableStream,
+    DiskFull,
+    PermissionDenied,
+    MissingFile,
+    UnknownEncoder,
+    MissingDecoder,
+    UnknownOption,
+    InvalidDimensions,
+    UnsupportedPixelFormat,
+    CodecContainerMismatch,
+    CorruptInput,
+}
+
+struct FailurePattern {
+    pattern: &'static str,
+    kind: FailureKind,
+    hint: &'static str,
+}
+
+const DECODE_ERROR: &str = "Error while decoding stream";
+
+// Checked in order, so the more specific patterns come first.
+const FAILURE_PATTERNS: &[FailurePattern] = &[
+    FailurePattern {
+        pattern: r"No space left on device",
+        kind: FailureKind::DiskFull,
+        hint: DISK_FULL_HINT,
+    },
+    FailurePattern {
+        pattern: r"Permission denied",
+        kind: FailureKind::PermissionDenied,
+        hint: PERMISSION_DENIED_HINT,
+    },
+    FailurePattern {
+        pattern: r"(?m)^.*No such file or directory.*$",
+        kind: FailureKind::MissingFile,
+        hint: "A file or directory ffmpeg needed does not exist.",
+    },
+    FailurePattern {
+        pattern: r"(?:Unknown encoder|Encoder not found|Requested encoder)\s*'?([\w-]*)'?",
+        kind: FailureKind::UnknownEncoder,
+        hint: "The bundled ffmpeg build does not include this encoder.",
+    },
+    FailurePattern {
+        pattern: r"(?:Decoder \(codec ([\w-]+)\) not found|Could not find codec parameters)",
+        kind: FailureKind::MissingDecoder,
+        hint: "The input uses a codec that ffmpeg cannot decode.",
+    },
+    FailurePattern {
+        pattern: r"Unrecognized option '([^']+)'|Option (\S+) not found",
+        kind: FailureKind::UnknownOption,
+        hint: "An option passed to ffmpeg is not supported by this build.",
+    },
+    FailurePattern {
+        pattern: r"Output file (?:#\d+ )?does not contain any stream|matches no streams",
+        kind: FailureKind::NoSuitableStream,
+        hint: "The input has no stream of the kind the output format needs, e.g. audio from a silent video.",
+    },
+    FailurePattern {
+        pattern: r"(?:width|height) not divisible by 2|Invalid (?:frame )?dimensions|Picture size \d+x\d+ is invalid|image dimensions? .* too large",
+        kind: FailureKind::InvalidDimensions,
+        hint: "Use even, non-zero resize dimensions within the encoder's limits.",
+    },
+    FailurePattern {
+        pattern: r"Incompatible pixel format|pixel format .* (?:is invalid or )?not supported|Unsupported pixel format|No pixel format specified",
+        kind: FailureKind::UnsupportedPixelFormat,
+        hint: "The source pixel format cannot be encoded to this output format.",
+    },
+    FailurePattern {
+        pattern: r"Could not find tag for codec|codec not currently supported in container|Only VP8 or VP9 or AV1 video and Vorbis or Opus audio",
+        kind: FailureKind::CodecContainerMismatch,
+        hint: "The selected codec cannot be stored in this container; pick a different output format.",
+    },
+    FailurePattern {
+        pattern: r"Invalid data found when processing input|moov atom not found|Error while decoding stream|corrupt (?:input|decoded frame)|Invalid NAL unit|partial file",
+        kind: FailureKind::CorruptInput,
+        hint: "The input file is damaged or incomplete; try re-downloading or remuxing it.",
+    },
+];
+
+fn compiled_patterns() -> &'static [(Regex, &'static FailurePattern)] {
+    static COMPILED: OnceLock<Vec<(Regex, &'static FailurePattern)>> = OnceLock::new();
+    COMPILED.get_or_init(|| {
+        FAILURE_PATTERNS
+            .iter()
+            .map(|p| {
+                (
+                    Regex::new(p.pattern).expect("Failed to compile ffmpeg failure pattern"),
+                    p,
+                )
+            })
+            .collect()
+    })
+}
+
+pub fn classify_failure(
+    log: &str,
+    exit_code: Option<i32>,
+    input_path: &Path,
+    output_path: &Path,
+) -> ConversionError {
+    let input = input_path.to_string_lossy().to_string();
+    let output = output_path.to_string_lossy().to_string();
+    let fatal_log = without_recovered_decode_errors(log);
+
+    for (re, pattern) in compiled_patterns() {
+        let Some(caps) = re.captures(&fatal_log) else {
+            continue;
+        };
+        let captured = caps
+            .iter()
+            .skip(1)
+            .flatten()
+            .map(|m| m.as_str().to_string())
+            .find(|s| !s.is_empty())
+            .unwrap_or_default();
+        let hint = pattern.hint.to_string();
+
+        return match pattern.kind {
+            FailureKind::DiskFull => ConversionError::DiskFull {
+                path: output,
+                hint,
+                log: log.to_string(),
+            },
+            FailureKind::PermissionDenied => ConversionError::PermissionDenied {
+                path: output,
+                hint,
+                log: log.to_string(),
+            },
+            FailureKind::MissingFile => {
+                let (path, specific_hint) = missing_path(&caps[0], input_path, output_path);
+                ConversionError::MissingFile {
+                    path,
+                    hint: specific_hint.map(str::to_string).unwrap_or(hint),
+                    log: log.to_string(),
+                }
+            }
+            FailureKind::NoSuitableStream => ConversionError::NoSuitableStream {
+                path: input,
+                hint,
+                log: log.to_string(),
+            },
+            FailureKind::UnknownEncoder => ConversionError::UnknownEncoder {
+                encoder: captured,
+                hint,
+                log: log.to_string(),
+            },
+            FailureKind::MissingDecoder => ConversionError::MissingDecoder {
+                codec: captured,
+                hint,
+                log: log.to_string(),
+            },
+            FailureKind::UnknownOption => ConversionError::UnknownOption {
+                option: captured,
+                hint,
+                log: log.to_string(),
+            },
+            FailureKind::InvalidDimensions => ConversionError::InvalidDimensions {
+                hint,
+                log: log.to_string(),
+            },
+            FailureKind::UnsupportedPixelFormat => ConversionError::UnsupportedPixelFormat {
+                hint,
+                log: log.to_string(),
+            },
+            FailureKind::CodecContainerMismatch => ConversionError::CodecContainerMismatch {
+                hint,
+                log: log.to_string(),
+            },
+            FailureKind::CorruptInput => ConversionError::CorruptInput {
+                path: input,
+                hint,
+                log: log.to_string(),
+            },
+        };
+    }
+
+    ConversionError::ProcessError {
+        tool: "ffmpeg".to_string(),
+        exit_code,
+        stderr: log.to_string(),
+    }
+}
+
+// ffmpeg logs decode errors and carries on, so one only explains the failure
+// when no other error was logged after it.
+fn without_recovered_decode_errors(log: &str) -> String {
+    let lines: Vec<&str> = log.lines().collect();
+    let last_error = lines
+        .iter()
+        .rposition(|line| line.to_ascii_lowercase().contains("error"));
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(i, line)| Some(*i) == last_error || !line.contains(DECODE_ERROR))
+        .map(|(_, line)| *line)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Works out which path a "No such file or directory" line is about, since
+// ffmpeg reports missing inputs and missing output directories the same way.
+fn missing_path(
+    line: &str,
+    input_path: &Path,
+    output_path: &Path,
+) -> (String, Option<&'static str>) {
+    let input = input_path.to_string_lossy();
+    if line.contains(input.as_ref()) {
+        return (
+            input.to_string(),
+            Some("The input file was moved or deleted before the conversion started."),
+        );
+    }
+    let output_dir = output_path.parent().unwrap_or(output_path);
+    let output_dir_str = output_dir.to_string_lossy();
+    if !output_dir_str.is_empty() && line.contains(output_dir_str.as_ref()) {
+        return (
+            output_dir_str.to_string(),
+            Some("The output directory does not exist or was removed during the conversion."),
+        );
+    }
+    let path = line
+        .split(": No such file or directory")
+        .next()
+        .unwrap_or(line)
+        .rsplit("] ")
+        .next()
+        .unwrap_or(line)
+        .trim();
+    (path.to_string(), None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DECODE_WARNING: &str =
+        "[h264 @ 0x1] Error while decoding stream #0:0: Invalid data found when processing input";
+
+    fn kind(log: &str) -> String {
+        let error = classify_failure(
+            log,
+            Some(1),
+            Path::new("/media/in.mp4"),
+            Path::new("/out/in.webm"),
+        );
+        serde_json::to_value(&error).unwrap()["type"]
+            .as_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn classifies_failures() {
+        let cases = [
+            (
+                "av_interleaved_write_frame(): No space left on device",
+                "DiskFull",
+            ),
+            ("/out/in.webm: Permission denied", "PermissionDenied"),
+            ("/media/in.mp4: No such file or directory", "MissingFile"),
+            ("Unknown encoder 'libfdk_aac'", "UnknownEncoder"),
+            ("Unrecognized option 'foo'.", "UnknownOption"),
+            (
+                "Output file #0 does not contain any stream",
+                "NoSuitableStream",
+            ),
+            (
+                "[libx264 @ 0x1] width not divisible by 2 (1279x720)",
+                "InvalidDimensions",
+            ),
+            (
+                "Could not find tag for codec h264 in stream #0",
+                "CodecContainerMismatch",
+            ),
+            (
+                "/media/in.mp4: Invalid data found when processing input",
+                "CorruptInput",
+            ),
+            ("[mov,mp4 @ 0x1] moov atom not found", "CorruptInput"),
+            ("Conversion failed!", "ProcessError"),
+        ];
+        for (log, expected) in cases {
+            assert_eq!(kind(log), expected, "{}", log);
+        }
+    }
+
+    #[test]
+    fn decode_errors_only_count_when_nothing_failed_after_them() {
+        let cases = [
+            (vec![DECODE_WARNING, "Conversion failed!"], "CorruptInput"),
+            (
+                vec![DECODE_WARNING, "frame=  120 fps=30", DECODE_WARNING],
+                "CorruptInput",
+            ),
+            (
+                vec![
+                    DECODE_WARNING,
+                    "av_interleaved_write_frame(): No space left on device",
+                ],
+                "DiskFull",
+            ),
+            (
+                vec![DECODE_WARNING, "/out/in.webm: Permission denied"],
+                "PermissionDenied",
+            ),
+            (
+                vec![
+                    DECODE_WARNING,
+                    "[libvpx @ 0x1] Error while encoding frame",
+                    "Conversion failed!",
+                ],
+                "ProcessError",
+            ),
+        ];
+        for (lines, expected) in cases {
+            let log = lines.join("\n");
+            assert_eq!(kind(&log), expected, "{}", log);
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use crate::error::ConversionError;
+use crate::modules::ffmpeg;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Manager};
+
+const LOG_DIR_NAME: &str = "logs";
+const LOG_EXT: &str = "log";
+
+pub const DEFAULT_MAX_LOG_AGE: Duration = Duration::from_secs(14 * 24 * 60 * 60);
+pub const DEFAULT_MAX_LOG_TOTAL_BYTES: u64 = 50 * 1024 * 1024;
+
+pub fn log_dir(app: &AppHandle) -> Result<PathBuf, ConversionError> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| ConversionError::IoError {
+            message: format!("Failed to resolve app data dir: {}", e),
+        })?;
+    Ok(data_dir.join(LOG_DIR_NAME))
+}
+
+pub fn log_path(dir: &Path, job_id: &str) -> PathBuf {
+    let safe_id: String = job_id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect();
+    let safe_id = if safe_id.is_empty() {
+        "job".to_string()
+    } else {
+        safe_id
+    };
+    dir.join(format!("{}.{}", safe_id, LOG_EXT))
+}
+
+// Writes are buffered and flushed when the log is dropped, so early returns
+// keep whatever ffmpeg printed before the failure.
+pub struct JobLog {
+    writer: BufWriter<File>,
+    finished: bool,
+}
+
+impl JobLog {
+    pub async fn create(app: &AppHandle, job_id: &str) -> Result<Self, ConversionError> {
+        let dir = log_dir(app)?;
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .map_err(|e| ConversionError::from_io(e, &dir))?;
+        let path = log_path(&dir, job_id);
+        rotate(&path).await;
+        let file = tokio::fs::File::create(&path)
+            .await
+            .map_err(|e| ConversionError::from_io(e, &path))?
+            .into_std()
+            .await;
+        Ok(Self {
+            writer: BufWriter::new(file),
+            finished: false,
+        })
+    }
+
+    pub fn write_command(&mut self, program: &Path, args: &[OsString]) {
+        let command_line = ffmpeg::format_command_line(program, args);
+        let started = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
+        self.write_line(&format!("# started: {}", started));
+        self.write_line(&format!("# command: {}", command_line));
+    }
+
+    pub fn write_line(&mut self, line: &str) {
+        let _ = writeln!(self.writer, "{}", line);
+    }
+
+    pub fn finish(mut self, exit_code: Option<i32>) {
+        self.write_line(&format!("# exit code: {:?}", exit_code));
+        self.finished = true;
+    }
+}
+
+impl Drop for JobLog {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.write_line("# ended before ffmpeg exited");
+        }
+        let _ = self.writer.flush();
+    }
+}
+
+// Keeps the previous log of a retried job under a timestamped name, so the
+// newest log stays at the plain `<id>.log` path.
+async fn rotate(path: &Path) {
+    if !tokio::fs::try_exists(path).await.unwrap_or(false) {
+        return;
+    }
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let rotated = path.with_file_name(format!(
+        "{}.{}.{}",
+        stem,
+        chrono::Local::now().format("%Y%m%d%H%M%S%3f"),
+        LOG_EXT
+    ));
+    let _ = tokio::fs::rename(path, rotated).await;
+}
+
+pub async fn read_job_log(app: &AppHandle, job_id: &str) -> Result<String, ConversionError> {
+    let path = log_path(&log_dir(app)?, job_id);
+    tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| ConversionError::from_io(e, &path))
+}
+
+pub async fn cleanup_job_logs(
+    app: &AppHandle,
+    max_age: Duration,
+    max_total_bytes: u64,
+) -> Result<usize, ConversionError> {
+    let dir = log_dir(app)?;
+    let mut entries = match tokio::fs::read_dir(&dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(ConversionError::from_io(e, &dir)),
+    };
+
+    let mut logs: Vec<(PathBuf, SystemTime, u64)> = Vec::new();
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some(LOG_EXT) {
+            continue;
+        }
+        if let Ok(metadata) = entry.metadata().await {
+            let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            logs.push((path, modified, metadata.len()));
+        }
+    }
+
+    // Newest first, so the size budget is spent on the most recent jobs.
+    logs.sort_by_key(|(_, modified, _)| std::cmp::Reverse(*modified));
+
+    let now = SystemTime::now();
+    let mut kept_bytes = 0u64;
+    let mut removed = 0usize;
+    for (path, modified, size) in logs {
+        let age = now.duration_since(modified).unwrap_or_default();
+        if age > max_age || kept_bytes + size > max_total_bytes {
+            if tokio::fs::remove_file(&path).await.is_ok() {
+                removed += 1;
+            }
+        } else {
+            kept_bytes += size;
+        }
+    }
+
+    Ok(removed)
+}
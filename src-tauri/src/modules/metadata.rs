@@ -0,0 +1,195 @@
+use crate::error::ConversionError;
+use crate::types::{Chapter, CoverArtSource, MetadataTag, StreamMetadata};
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+fn stream_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"^Stream #0:(\d+)[^:]*: (\w+): ([^\s,]+)")
+            .expect("Failed to compile stream regex")
+    })
+}
+
+fn chapter_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"^Chapter #0:\d+: start (-?[\d.]+), end (-?[\d.]+)")
+            .expect("Failed to compile chapter regex")
+    })
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MediaTags {
+    pub format: BTreeMap<String, String>,
+    pub streams: Vec<StreamMetadata>,
+    pub chapters: Vec<Chapter>,
+}
+
+#[derive(Clone, Copy)]
+enum Target {
+    Format,
+    Stream,
+    Chapter,
+}
+
+// Reads the tags, streams and chapters of `Input #0` from the summary ffmpeg
+// prints for `-i <file>`. Each `Metadata:` block belongs to the line above it.
+pub fn parse_media_tags(log: &str) -> MediaTags {
+    let mut parsed = MediaTags::default();
+    let mut target = Target::Format;
+    let mut metadata_indent: Option<usize> = None;
+    let mut last_key = String::new();
+
+    let lines = log
+        .lines()
+        .skip_while(|l| !l.starts_with("Input #0"))
+        .skip(1)
+        .take_while(|l| l.starts_with(' '));
+
+    for line in lines {
+        let indent = line.len() - line.trim_start().len();
+        let trimmed = line.trim();
+
+        if let Some(header_indent) = metadata_indent {
+            if indent > header_indent {
+                if let Some((key, value)) = line.split_once(':') {
+                    let value = value.strip_prefix(' ').unwrap_or(value);
+                    let key = key.trim();
+                    if key.is_empty() {
+                        // Continuation of a multi-line value.
+                        append_tag(&mut parsed, target, &last_key, value, true);
+                    } else {
+                        last_key = key.to_string();
+                        append_tag(&mut parsed, target, key, value, false);
+                    }
+                }
+                continue;
+            }
+            metadata_indent = None;
+        }
+
+        if trimmed == "Metadata:" {
+            if indent <= 2 {
+                target = Target::Format;
+            }
+            metadata_indent = Some(indent);
+        } else if let Some(caps) = chapter_regex().captures(trimmed) {
+            parsed.chapters.push(Chapter {
+                start_secs: caps[1].parse().unwrap_or_default(),
+                end_secs: caps[2].parse().unwrap_or_default(),
+                title: None,
+            });
+            target = Target::Chapter;
+        } else if let Some(caps) = stream_regex().captures(trimmed) {
+            parsed.streams.push(StreamMetadata {
+                index: caps[1].parse().unwrap_or_default(),
+                kind: caps[2].to_lowercase(),
+                codec: caps[3].to_string(),
+                attached_pic: trimmed.contains("(attached pic)"),
+                tags: BTreeMap::new(),
+            });
+            target = Target::Stream;
+        }
+    }
+
+    parsed
+}
+
+fn append_tag(parsed: &mut MediaTags, target: Target, key: &str, value: &str, continuation: bool) {
+    let slot = match target {
+        Target::Format => parsed.format.entry(key.to_string()).or_default(),
+        Target::Stream => match parsed.streams.last_mut() {
+            Some(stream) => stream.tags.entry(key.to_string()).or_default(),
+            None => return,
+        },
+        Target::Chapter => match parsed.chapters.last_mut() {
+            Some(chapter) if key == "title" => chapter.title.get_or_insert_with(String::new),
+            _ => return,
+        },
+    };
+    if continuation {
+        slot.push('\n');
+    }
+    slot.push_str(value);
+}
+
+pub fn parse_global_tags(log: &str) -> BTreeMap<String, String> {
+    parse_media_tags(log).format
+}
+
+pub fn filter_tags(tags: &BTreeMap<String, String>, keep: &[MetadataTag]) -> Vec<(String, String)> {
+    tags.iter()
+        .filter(|(key, _)| {
+            let key = key.to_lowercase();
+            keep.iter()
+                .any(|tag| tag.ffmpeg_keys().contains(&key.as_str()))
+        })
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect()
+}
+
+const COVER_PREFIX: &str = "aether_cover_";
+const DEFAULT_COVER_FRAME_SECS: f64 = 1.0;
+
+pub fn cover_frame_path(file_id: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("{}{}.jpg", COVER_PREFIX, file_id))
+}
+
+pub fn cover_frame_time(cover: &CoverArtSource) -> Option<f64> {
+    match cover {
+        CoverArtSource::VideoFrame { time_secs } => {
+            Some(time_secs.unwrap_or(DEFAULT_COVER_FRAME_SECS))
+        }
+        CoverArtSource::Image { .. } => None,
+    }
+}
+
+// The image that ends up embedded; video frames are extracted to a temp file
+// before the conversion runs.
+pub fn cover_art_path(cover: &CoverArtSource, file_id: &str) -> PathBuf {
+    match cover {
+        CoverArtSource::Image { path } => PathBuf::from(path),
+        CoverArtSource::VideoFrame { .. } => cover_frame_path(file_id),
+    }
+}
+
+pub async fn copy_file_times(source: &Path, target: &Path) -> Result<(), ConversionError> {
+    let metadata = tokio::fs::metadata(source)
+        .await
+        .map_err(|e| ConversionError::from_io(e, source))?;
+
+    let mut times = std::fs::FileTimes::new();
+    if let Ok(modified) = metadata.modified() {
+        times = times.set_modified(modified);
+    }
+    if let Ok(accessed) = metadata.accessed() {
+        times = times.set_accessed(accessed);
+    }
+    // Creation time can only be set on Windows and macOS.
+    #[cfg(windows)]
+    if let Ok(created) = metadata.created() {
+        use std::os::windows::fs::FileTimesExt;
+        times = times.set_created(created);
+    }
+    #[cfg(target_os = "macos")]
+    if let Ok(created) = metadata.created() {
+        use std::os::macos::fs::FileTimesExt;
+        times = times.set_created(created);
+    }
+
+    let target = target.to_path_buf();
+    tauri::async_runtime::spawn_blocking(move || {
+        std::fs::File::options()
+            .write(true)
+            .open(&target)
+            .and_then(|file| file.set_times(times))
+            .map_err(|e| ConversionError::from_io(e, &target))
+    })
+    .await
+    .map_err(|e| ConversionError::Unknown {
+        message: format!("Failed to copy file times: {}", e),
+    })?
+}
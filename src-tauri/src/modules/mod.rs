@@ -1,3 +1,15 @@
+pub mod chapters;
+pub mod exif;
 pub mod ffmpeg;
+pub mod ffmpeg_errors;
+pub mod job_log;
+pub mod metadata;
 pub mod naming;
+pub mod presets;
+pub mod scanner;
+pub mod source_action;
+pub mod temp_files;
 pub mod thumbnail;
+pub mod validation;
+pub mod verification;
+pub mod watcher;
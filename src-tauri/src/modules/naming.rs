@@ -1,43 +1,662 @@
-use crate::types::{NamingBlock, NamingConfig};
+use crate::error::ConversionError;
+use crate::types::{
+    CaseTransform, MetadataField, NamingBlock, NamingConfig, RenameRule, SanitizeMode,
+};
+use chrono::NaiveDateTime;
 use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Mutex, OnceLock};
 
-pub fn apply_naming_pipeline(original_stem: &str, config: &NamingConfig) -> String {
+const MAX_COUNTER_PADDING: usize = 12;
+const DEFAULT_RANDOM_LENGTH: usize = 8;
+const MIN_RANDOM_LENGTH: usize = 4;
+const MAX_RANDOM_LENGTH: usize = 32;
+const DATE_TAKEN_FORMAT: &str = "%Y%m%d%H%M%S";
+const MAX_CACHED_REGEXES: usize = 64;
+
+#[derive(Debug, Clone, Default)]
+pub struct NamingContext {
+    pub original_stem: String,
+    pub original_extension: String,
+    pub parent_folder: Option<String>,
+    pub output_extension: String,
+    pub preset_name: Option<String>,
+    pub batch_index: u64,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub duration_secs: Option<f64>,
+    pub date_taken: Option<NaiveDateTime>,
+    pub camera: Option<String>,
+    /// Container tags with lowercased keys.
+    pub tags: BTreeMap<String, String>,
+}
+
+impl NamingContext {
+    fn counter(&self, start: u64, step: u64, padding: usize) -> String {
+        let value = start.saturating_add(step.saturating_mul(self.batch_index));
+        let width = padding.min(MAX_COUNTER_PADDING);
+        format!("{:0width$}", value, width = width)
+    }
+
+    fn resolution(&self) -> Option<String> {
+        self.width
+            .zip(self.height)
+            .map(|(w, h)| format!("{}x{}", w, h))
+    }
+
+    fn duration(&self) -> Option<String> {
+        self.duration_secs.filter(|d| *d > 0.0).map(format_duration)
+    }
+
+    fn metadata(&self, field: MetadataField) -> Option<String> {
+        let tag = |keys: &[&str]| {
+            keys.iter()
+                .filter_map(|k| self.tags.get(*k))
+                .map(|v| v.trim())
+                .find(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+        match field {
+            MetadataField::DateTaken => self
+                .date_taken
+                .and_then(|d| format_naive_date(&d, DATE_TAKEN_FORMAT)),
+            MetadataField::Title => tag(&["title"]),
+            MetadataField::Artist => tag(&["artist", "album_artist"]),
+            MetadataField::Album => tag(&["album"]),
+            MetadataField::Camera => self.camera.clone(),
+        }
+    }
+}
+
+pub fn needs_media_info(config: &NamingConfig) -> bool {
+    if let Some(template) = template_source(config) {
+        return NameTemplate::parse(template).is_ok_and(|t| t.needs_media_info());
+    }
+    config.blocks.iter().any(|block| {
+        matches!(
+            block,
+            NamingBlock::Resolution { .. }
+                | NamingBlock::Duration { .. }
+                | NamingBlock::Metadata { .. }
+        )
+    })
+}
+
+pub fn validate_naming_config(config: &NamingConfig) -> Result<(), ConversionError> {
+    if config.separator.contains(is_illegal_char) {
+        return Err(ConversionError::InvalidConfig {
+            message: format!(
+                "naming_config.separator: '{}' contains characters not allowed in file names",
+                config.separator
+            ),
+        });
+    }
+    if let Some(template) = template_source(config) {
+        NameTemplate::parse(template)?;
+    }
+    for (index, rule) in config.rules.iter().enumerate() {
+        validate_rule(rule).map_err(|message| ConversionError::InvalidConfig {
+            message: format!("naming_config.rules[{}]: {}", index, message),
+        })?;
+    }
+    Ok(())
+}
+
+fn validate_rule(rule: &RenameRule) -> Result<(), String> {
+    match rule {
+        RenameRule::Replace { find, .. } if find.is_empty() => {
+            Err("find text must not be empty".to_string())
+        }
+        RenameRule::Replace { replace, .. } | RenameRule::Regex { replace, .. }
+            if replace.contains(is_illegal_char) =>
+        {
+            Err(format!(
+                "replacement '{}' contains characters not allowed in file names",
+                replace
+            ))
+        }
+        RenameRule::Regex { pattern, .. } => Regex::new(pattern)
+            .map(|_| ())
+            .map_err(|e| format!("invalid pattern '{}': {}", pattern, e)),
+        RenameRule::Truncate { max_length: 0 } => {
+            Err("max_length must be greater than 0".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Runs the rename rules over `name` in order. Invalid regex rules are
+/// rejected during validation and skipped here.
+pub fn apply_rename_rules(name: &str, rules: &[RenameRule]) -> String {
+    rules
+        .iter()
+        .fold(name.to_string(), |name, rule| match rule {
+            RenameRule::Replace { find, replace } if !find.is_empty() => {
+                name.replace(find.as_str(), replace)
+            }
+            RenameRule::Replace { .. } => name,
+            RenameRule::Regex { pattern, replace } => match cached_regex(pattern) {
+                Some(re) => re.replace_all(&name, replace.as_str()).into_owned(),
+                None => name,
+            },
+            RenameRule::Trim { chars } if chars.is_empty() => name.trim().to_string(),
+            RenameRule::Trim { chars } => name.trim_matches(|c| chars.contains(c)).to_string(),
+            RenameRule::Truncate { max_length } => name.chars().take(*max_length).collect(),
+            RenameRule::Case { case } => apply_case(&name, *case),
+        })
+}
+
+// Rules run once per file, so each pattern is compiled once and reused by
+// the rest of the batch.
+fn cached_regex(pattern: &str) -> Option<Regex> {
+    static CACHE: OnceLock<Mutex<HashMap<String, Regex>>> = OnceLock::new();
+    let cache = CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut cache = cache.lock().unwrap_or_else(|e| e.into_inner());
+
+    if let Some(re) = cache.get(pattern) {
+        return Some(re.clone());
+    }
+    let re = Regex::new(pattern).ok()?;
+    if cache.len() >= MAX_CACHED_REGEXES {
+        cache.clear();
+    }
+    cache.insert(pattern.to_string(), re.clone());
+    Some(re)
+}
+
+fn template_source(config: &NamingConfig) -> Option<&str> {
+    config.template.as_deref().filter(|t| !t.trim().is_empty())
+}
+
+pub fn apply_naming_pipeline(ctx: &NamingContext, config: &NamingConfig) -> String {
+    let renamed;
+    let ctx = if config.rules.is_empty() {
+        ctx
+    } else {
+        renamed = NamingContext {
+            original_stem: apply_rename_rules(&ctx.original_stem, &config.rules),
+            ..ctx.clone()
+        };
+        &renamed
+    };
+
+    // Templates are validated with the request, so a parse failure here only
+    // falls back to the blocks.
+    let template = template_source(config).and_then(|t| NameTemplate::parse(t).ok());
+    let mut result = match template {
+        Some(template) => template.render(ctx),
+        None => render_blocks(ctx, config),
+    };
+
+    if config.sanitize_enabled {
+        result = sanitize_filename(&result, config.sanitize_mode, config.preserve_case);
+    }
+
+    if result.is_empty() {
+        "unnamed".to_string()
+    } else {
+        result
+    }
+}
+
+fn render_blocks(ctx: &NamingContext, config: &NamingConfig) -> String {
     let mut parts: Vec<String> = Vec::new();
 
     for block in &config.blocks {
-        match block {
-            NamingBlock::Original => {
-                parts.push(original_stem.to_string());
+        let value = match block {
+            NamingBlock::Original => Some(ctx.original_stem.clone()),
+            NamingBlock::Prefix { value } => Some(value.clone()),
+            NamingBlock::Random { length } => {
+                let len = (*length as usize).clamp(MIN_RANDOM_LENGTH, MAX_RANDOM_LENGTH);
+                Some(generate_random_string(len))
             }
-            NamingBlock::Prefix { value } => {
-                if !value.is_empty() {
-                    parts.push(value.clone());
+            NamingBlock::Date => Some(format_date_timestamp()),
+            NamingBlock::Counter {
+                start,
+                step,
+                padding,
+            } => Some(ctx.counter(*start, *step, *padding as usize)),
+            NamingBlock::Resolution { fallback } => {
+                ctx.resolution().or_else(|| Some(fallback.clone()))
+            }
+            NamingBlock::Duration { fallback } => ctx.duration().or_else(|| Some(fallback.clone())),
+            NamingBlock::Format => Some(ctx.output_extension.clone()),
+            NamingBlock::Preset { fallback } => {
+                ctx.preset_name.clone().or_else(|| Some(fallback.clone()))
+            }
+            NamingBlock::Extension => Some(ctx.original_extension.clone()),
+            NamingBlock::ParentFolder { fallback } => {
+                ctx.parent_folder.clone().or_else(|| Some(fallback.clone()))
+            }
+            NamingBlock::Metadata { field, fallback } => {
+                ctx.metadata(*field).or_else(|| Some(fallback.clone()))
+            }
+        };
+
+        if let Some(value) = value.map(|v| clean_field(&v)).filter(|v| !v.is_empty()) {
+            parts.push(value);
+        }
+    }
+
+    parts.join(&config.separator)
+}
+
+const ILLEGAL_NAME_CHARS: &str = "/\\:*?\"<>|";
+
+fn is_illegal_char(c: char) -> bool {
+    ILLEGAL_NAME_CHARS.contains(c) || c.is_control()
+}
+
+// Tags, folder names and rename rules can hold anything, so every rendered
+// value is made safe for a single path component even with sanitizing off.
+fn clean_field(value: &str) -> String {
+    let mut cleaned: String = value.chars().filter(|c| !is_illegal_char(*c)).collect();
+    while cleaned.contains("..") {
+        cleaned = cleaned.replace("..", ".");
+    }
+    cleaned
+}
+
+const PLACEHOLDERS: &[&str] = &[
+    "original",
+    "ext",
+    "format",
+    "preset",
+    "parent",
+    "w",
+    "h",
+    "resolution",
+    "duration",
+    "date",
+    "date_taken",
+    "counter",
+    "random",
+    "title",
+    "artist",
+    "album",
+    "camera",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Literal(String),
+    Field {
+        field: Field,
+        transforms: Vec<CaseTransform>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Field {
+    Original,
+    Extension,
+    Format,
+    Preset,
+    ParentFolder,
+    Width,
+    Height,
+    Resolution,
+    Duration,
+    Date(Option<String>),
+    DateTaken(Option<String>),
+    Counter {
+        padding: usize,
+        start: u64,
+        step: u64,
+    },
+    Random(usize),
+    Metadata(MetadataField),
+}
+
+// `{date:%Y-%m-%d}-{original}-{counter:03}`; `{{` and `}}` are literal braces.
+#[derive(Debug, Clone)]
+pub struct NameTemplate {
+    segments: Vec<Segment>,
+}
+
+impl NameTemplate {
+    pub fn parse(template: &str) -> Result<Self, ConversionError> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().enumerate().peekable();
+
+        while let Some((pos, c)) = chars.next() {
+            match c {
+                '{' | '}' if chars.peek().map(|(_, next)| *next) == Some(c) => {
+                    chars.next();
+                    literal.push(c);
+                }
+                '{' => {
+                    let mut body = String::new();
+                    loop {
+                        match chars.next() {
+                            Some((_, '}')) => break,
+                            Some((_, '{')) | None => {
+                                return Err(template_error(format!(
+                                    "'{{' at position {} is not closed",
+                                    pos + 1
+                                )))
+                            }
+                            Some((_, c)) => body.push(c),
+                        }
+                    }
+                    push_literal(&mut segments, &mut literal)?;
+                    segments.push(parse_placeholder(&body)?);
                 }
+                '}' => {
+                    return Err(template_error(format!(
+                        "unmatched '}}' at position {}",
+                        pos + 1
+                    )))
+                }
+                c => literal.push(c),
             }
-            NamingBlock::Random { length } => {
-                let len = (*length as usize).clamp(4, 32);
-                parts.push(generate_random_string(len));
+        }
+        push_literal(&mut segments, &mut literal)?;
+
+        if segments.is_empty() {
+            return Err(template_error("template is empty".to_string()));
+        }
+        Ok(Self { segments })
+    }
+
+    fn needs_media_info(&self) -> bool {
+        self.segments.iter().any(|segment| {
+            matches!(
+                segment,
+                Segment::Field {
+                    field: Field::Width
+                        | Field::Height
+                        | Field::Resolution
+                        | Field::Duration
+                        | Field::DateTaken(_)
+                        | Field::Metadata(_),
+                    ..
+                }
+            )
+        })
+    }
+
+    pub fn render(&self, ctx: &NamingContext) -> String {
+        let mut out = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => out.push_str(text),
+                Segment::Field { field, transforms } => {
+                    let value = clean_field(&render_field(field, ctx).unwrap_or_default());
+                    let value = transforms
+                        .iter()
+                        .fold(value, |value, case| apply_case(&value, *case));
+                    out.push_str(&value);
+                }
             }
-            NamingBlock::Date => {
-                parts.push(format_date_timestamp());
+        }
+        out
+    }
+}
+
+fn push_literal(segments: &mut Vec<Segment>, literal: &mut String) -> Result<(), ConversionError> {
+    if literal.is_empty() {
+        return Ok(());
+    }
+    if literal.contains(is_illegal_char) {
+        return Err(template_error(format!(
+            "'{}' contains characters not allowed in file names",
+            literal
+        )));
+    }
+    segments.push(Segment::Literal(std::mem::take(literal)));
+    Ok(())
+}
+
+fn parse_placeholder(body: &str) -> Result<Segment, ConversionError> {
+    let mut pieces = body.split('|');
+    let head = pieces.next().unwrap_or_default();
+    let (name, arg) = match head.split_once(':') {
+        Some((name, arg)) => (name.trim(), Some(arg)),
+        None => (head.trim(), None),
+    };
+
+    if !PLACEHOLDERS.contains(&name) {
+        return Err(template_error(format!(
+            "unknown placeholder {{{}}}; expected one of {}",
+            name,
+            PLACEHOLDERS.join(", ")
+        )));
+    }
+    if arg.is_some() && !matches!(name, "date" | "date_taken" | "counter" | "random") {
+        return Err(template_error(format!(
+            "{{{}}} does not take an argument",
+            name
+        )));
+    }
+
+    let field = match name {
+        "original" => Field::Original,
+        "ext" => Field::Extension,
+        "format" => Field::Format,
+        "preset" => Field::Preset,
+        "parent" => Field::ParentFolder,
+        "w" => Field::Width,
+        "h" => Field::Height,
+        "resolution" => Field::Resolution,
+        "duration" => Field::Duration,
+        "date" => Field::Date(arg.map(parse_date_format).transpose()?),
+        "date_taken" => Field::DateTaken(arg.map(parse_date_format).transpose()?),
+        "counter" => parse_counter(arg.unwrap_or_default())?,
+        "random" => Field::Random(parse_random_length(arg)?),
+        "title" => Field::Metadata(MetadataField::Title),
+        "artist" => Field::Metadata(MetadataField::Artist),
+        "album" => Field::Metadata(MetadataField::Album),
+        _ => Field::Metadata(MetadataField::Camera),
+    };
+
+    let transforms = pieces
+        .map(|t| parse_case(t.trim()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Segment::Field { field, transforms })
+}
+
+fn parse_date_format(format: &str) -> Result<String, ConversionError> {
+    use chrono::format::{Item, StrftimeItems};
+
+    let sample = chrono::NaiveDate::from_ymd_opt(2000, 1, 1)
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .unwrap_or_default();
+    let rendered = format_naive_date(&sample, format);
+    if format.is_empty()
+        || StrftimeItems::new(format).any(|item| matches!(item, Item::Error))
+        || rendered.is_none()
+    {
+        return Err(template_error(format!(
+            "'{}' is not a valid date format",
+            format
+        )));
+    }
+    if rendered.is_some_and(|r| r.contains(is_illegal_char)) {
+        return Err(template_error(format!(
+            "date format '{}' produces characters not allowed in file names",
+            format
+        )));
+    }
+    Ok(format.to_string())
+}
+
+// `{counter:PAD[:START[:STEP]]}`, e.g. `{counter:03}` or `{counter:4:100:10}`.
+fn parse_counter(arg: &str) -> Result<Field, ConversionError> {
+    let mut parts = arg.split(':').map(str::trim);
+    let mut number = |label: &str, default: u64| -> Result<u64, ConversionError> {
+        match parts.next().filter(|p| !p.is_empty()) {
+            Some(value) => value.parse().map_err(|_| {
+                template_error(format!("counter {} '{}' is not a number", label, value))
+            }),
+            None => Ok(default),
+        }
+    };
+    let padding = number("padding", 0)? as usize;
+    let start = number("start", 1)?;
+    let step = number("step", 1)?;
+    if padding > MAX_COUNTER_PADDING {
+        return Err(template_error(format!(
+            "counter padding {} is larger than {}",
+            padding, MAX_COUNTER_PADDING
+        )));
+    }
+    Ok(Field::Counter {
+        padding,
+        start,
+        step,
+    })
+}
+
+fn parse_random_length(arg: Option<&str>) -> Result<usize, ConversionError> {
+    let Some(arg) = arg else {
+        return Ok(DEFAULT_RANDOM_LENGTH);
+    };
+    match arg.trim().parse::<usize>() {
+        Ok(length) if (MIN_RANDOM_LENGTH..=MAX_RANDOM_LENGTH).contains(&length) => Ok(length),
+        _ => Err(template_error(format!(
+            "random length '{}' must be between {} and {}",
+            arg, MIN_RANDOM_LENGTH, MAX_RANDOM_LENGTH
+        ))),
+    }
+}
+
+fn parse_case(name: &str) -> Result<CaseTransform, ConversionError> {
+    match name {
+        "lower" => Ok(CaseTransform::Lower),
+        "upper" => Ok(CaseTransform::Upper),
+        "title" => Ok(CaseTransform::Title),
+        "kebab" => Ok(CaseTransform::Kebab),
+        "snake" => Ok(CaseTransform::Snake),
+        _ => Err(template_error(format!(
+            "unknown transform '|{}'; expected lower, upper, title, kebab or snake",
+            name
+        ))),
+    }
+}
+
+fn render_field(field: &Field, ctx: &NamingContext) -> Option<String> {
+    match field {
+        Field::Original => Some(ctx.original_stem.clone()),
+        Field::Extension => Some(ctx.original_extension.clone()),
+        Field::Format => Some(ctx.output_extension.clone()),
+        Field::Preset => ctx.preset_name.clone(),
+        Field::ParentFolder => ctx.parent_folder.clone(),
+        Field::Width => ctx.width.map(|w| w.to_string()),
+        Field::Height => ctx.height.map(|h| h.to_string()),
+        Field::Resolution => ctx.resolution(),
+        Field::Duration => ctx.duration(),
+        Field::Date(None) => Some(format_date_timestamp()),
+        Field::Date(Some(format)) => format_naive_date(&chrono::Local::now().naive_local(), format),
+        Field::DateTaken(format) => ctx
+            .date_taken
+            .and_then(|d| format_naive_date(&d, format.as_deref().unwrap_or(DATE_TAKEN_FORMAT))),
+        Field::Counter {
+            padding,
+            start,
+            step,
+        } => Some(ctx.counter(*start, *step, *padding)),
+        Field::Random(length) => Some(generate_random_string(*length)),
+        Field::Metadata(field) => ctx.metadata(*field),
+    }
+}
+
+// Formatting goes through `write!` because chrono reports unsupported
+// specifiers (such as `%z` on a naive date) as a formatting error.
+fn format_naive_date(date: &NaiveDateTime, format: &str) -> Option<String> {
+    use std::fmt::Write;
+
+    let mut out = String::new();
+    write!(out, "{}", date.format(format)).ok()?;
+    Some(out)
+}
+
+pub fn apply_case(value: &str, case: CaseTransform) -> String {
+    match case {
+        CaseTransform::Lower => value.to_lowercase(),
+        CaseTransform::Upper => value.to_uppercase(),
+        CaseTransform::Title => {
+            let mut out = String::with_capacity(value.len());
+            let mut word_start = true;
+            for c in value.chars() {
+                if word_start {
+                    out.extend(c.to_uppercase());
+                } else {
+                    out.extend(c.to_lowercase());
+                }
+                word_start = !c.is_alphanumeric();
             }
+            out
         }
+        CaseTransform::Kebab => split_words(value).join("-"),
+        CaseTransform::Snake => split_words(value).join("_"),
     }
+}
 
-    let mut result = parts.join("_");
+// Lowercase words split on punctuation, whitespace and camelCase boundaries.
+fn split_words(value: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut prev_lower = false;
 
-    if config.sanitize_enabled {
-        result = sanitize_filename(&result);
+    for c in value.chars() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            prev_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && prev_lower {
+            words.push(std::mem::take(&mut current));
+        }
+        prev_lower = c.is_lowercase() || c.is_numeric();
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
     }
+    words
+}
 
-    if result.is_empty() {
-        "unnamed".to_string()
+fn template_error(message: String) -> ConversionError {
+    ConversionError::InvalidConfig {
+        message: format!("naming_config.template: {}", message),
+    }
+}
+
+// Compact durations such as `45s`, `3m07s` or `1h02m03s`.
+fn format_duration(secs: f64) -> String {
+    let total = secs.round() as u64;
+    let (h, m, s) = (total / 3600, total / 60 % 60, total % 60);
+    if h > 0 {
+        format!("{}h{:02}m{:02}s", h, m, s)
+    } else if m > 0 {
+        format!("{}m{:02}s", m, s)
     } else {
-        result
+        format!("{}s", s)
     }
 }
 
+// EXIF (`2023:07:14 10:22:01`) or ISO 8601.
+pub fn parse_metadata_date(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim().trim_end_matches('Z');
+    [
+        "%Y:%m:%d %H:%M:%S",
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+    .or_else(|| {
+        chrono::DateTime::parse_from_rfc3339(value)
+            .ok()
+            .map(|d| d.naive_local())
+    })
+}
+
 fn format_date_timestamp() -> String {
     static LAST: OnceLock<Mutex<(String, u32)>> = OnceLock::new();
     let state = LAST.get_or_init(|| Mutex::new((String::new(), 0)));
@@ -65,25 +684,385 @@ fn generate_random_string(length: usize) -> String {
         .collect()
 }
 
-fn sanitize_filename(name: &str) -> String {
+const WINDOWS_RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+fn sanitize_filename(name: &str, mode: SanitizeMode, preserve_case: bool) -> String {
+    let cased = if preserve_case {
+        name.to_string()
+    } else {
+        name.to_lowercase()
+    };
+
+    let sanitized = match mode {
+        SanitizeMode::Ascii => sanitize_ascii(&cased),
+        SanitizeMode::Transliterate => sanitize_ascii(&transliterate(&cased)),
+        SanitizeMode::Unicode => sanitize_unicode(&cased),
+    };
+
+    if sanitized.is_empty() {
+        "file".to_string()
+    } else {
+        avoid_reserved_name(sanitized)
+    }
+}
+
+fn sanitize_ascii(name: &str) -> String {
     static RE: OnceLock<Regex> = OnceLock::new();
     let re =
         RE.get_or_init(|| Regex::new(r"[^a-zA-Z0-9_-]").expect("Failed to compile sanitize regex"));
 
-    let lowered = name.to_lowercase().replace(' ', "_");
-
-    let sanitized = re.replace_all(&lowered, "").to_string();
+    let sanitized = re.replace_all(&name.replace(' ', "_"), "").to_string();
 
     static RE_MULTI: OnceLock<Regex> = OnceLock::new();
     let re_multi = RE_MULTI
         .get_or_init(|| Regex::new(r"_+").expect("Failed to compile multi-underscore regex"));
     let cleaned = re_multi.replace_all(&sanitized, "_").to_string();
 
-    let trimmed = cleaned.trim_matches('_').to_string();
+    cleaned.trim_matches('_').to_string()
+}
 
-    if trimmed.is_empty() {
-        "file".to_string()
+// Windows also rejects names ending in a dot or space.
+fn sanitize_unicode(name: &str) -> String {
+    let stripped: String = name.chars().filter(|c| !is_illegal_char(*c)).collect();
+    stripped.trim().trim_end_matches(['.', ' ']).to_string()
+}
+
+// `CON`, `nul.txt` and friends are device names on Windows, whatever the case
+// or extension.
+fn avoid_reserved_name(name: String) -> String {
+    let base = name.split('.').next().unwrap_or_default();
+    if WINDOWS_RESERVED_NAMES
+        .iter()
+        .any(|reserved| base.eq_ignore_ascii_case(reserved))
+    {
+        format!("{}_", name)
     } else {
-        trimmed
+        name
+    }
+}
+
+fn transliterate(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii() {
+            out.push(c);
+            continue;
+        }
+        if let Some(ascii) = transliterate_char(c) {
+            out.push_str(ascii);
+            continue;
+        }
+        let mut lower = c.to_lowercase();
+        let ascii = match (lower.next(), lower.next()) {
+            (Some(l), None) if l != c => transliterate_char(l),
+            _ => None,
+        };
+        match ascii {
+            // Uppercase source letter: capitalize the first output letter.
+            Some(ascii) => {
+                let mut chars = ascii.chars();
+                out.extend(chars.next().map(|f| f.to_ascii_uppercase()));
+                out.push_str(chars.as_str());
+            }
+            // Left for the ASCII filter to drop.
+            None => out.push(c),
+        }
+    }
+    out
+}
+
+// Lowercase letters, plus `İ` whose lowercase form is two characters; other
+// uppercase letters are mapped through their lowercase form by `transliterate`.
+fn transliterate_char(c: char) -> Option<&'static str> {
+    Some(match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'æ' => "ae",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'ď' | 'đ' | 'ð' => "d",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'ĥ' | 'ħ' => "h",
+        'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+        'İ' => "I",
+        'ĳ' => "ij",
+        'ĵ' => "j",
+        'ķ' => "k",
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+        'ñ' | 'ń' | 'ņ' | 'ň' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+        'œ' => "oe",
+        'ŕ' | 'ŗ' | 'ř' => "r",
+        'ś' | 'ŝ' | 'ş' | 'š' | 'ș' => "s",
+        'ß' => "ss",
+        'ţ' | 'ť' | 'ŧ' | 'ț' => "t",
+        'þ' => "th",
+        'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+        'ŵ' => "w",
+        'ý' | 'ÿ' | 'ŷ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        // Cyrillic
+        'а' => "a",
+        'б' => "b",
+        'в' => "v",
+        'г' | 'ґ' => "g",
+        'д' => "d",
+        'е' | 'є' => "e",
+        'ё' => "yo",
+        'ж' => "zh",
+        'з' => "z",
+        'и' | 'і' => "i",
+        'ї' => "yi",
+        'й' => "y",
+        'к' => "k",
+        'л' => "l",
+        'м' => "m",
+        'н' => "n",
+        'о' => "o",
+        'п' => "p",
+        'р' => "r",
+        'с' => "s",
+        'т' => "t",
+        'у' => "u",
+        'ф' => "f",
+        'х' => "kh",
+        'ц' => "ts",
+        'ч' => "ch",
+        'ш' => "sh",
+        'щ' => "shch",
+        'ъ' | 'ь' => "",
+        'ы' => "y",
+        'э' => "e",
+        'ю' => "yu",
+        'я' => "ya",
+        // Greek
+        'α' | 'ά' => "a",
+        'β' => "v",
+        'γ' => "g",
+        'δ' => "d",
+        'ε' | 'έ' => "e",
+        'ζ' => "z",
+        'η' | 'ή' => "i",
+        'θ' => "th",
+        'ι' | 'ί' | 'ϊ' => "i",
+        'κ' => "k",
+        'λ' => "l",
+        'μ' => "m",
+        'ν' => "n",
+        'ξ' => "x",
+        'ο' | 'ό' => "o",
+        'π' => "p",
+        'ρ' => "r",
+        'σ' | 'ς' => "s",
+        'τ' => "t",
+        'υ' | 'ύ' | 'ϋ' => "y",
+        'φ' => "f",
+        'χ' => "ch",
+        'ψ' => "ps",
+        'ω' | 'ώ' => "o",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn malicious_title_stays_inside_the_output_directory() {
+        let ctx = NamingContext {
+            original_stem: "clip".to_string(),
+            tags: BTreeMap::from([(
+                "title".to_string(),
+                "../../..\\Windows/System32: \"AC/DC\" <live>".to_string(),
+            )]),
+            ..Default::default()
+        };
+        let blocks = NamingConfig {
+            blocks: vec![NamingBlock::Metadata {
+                field: MetadataField::Title,
+                fallback: String::new(),
+            }],
+            ..Default::default()
+        };
+        let template = NamingConfig {
+            template: Some("{title}-{original}".to_string()),
+            ..Default::default()
+        };
+
+        for config in [&blocks, &template] {
+            let name = apply_naming_pipeline(&ctx, config);
+            assert!(!name.contains(is_illegal_char), "{}", name);
+            assert!(!name.contains(".."), "{}", name);
+        }
+        assert_eq!(
+            apply_naming_pipeline(&ctx, &template),
+            ".WindowsSystem32 ACDC live-clip"
+        );
+    }
+
+    fn parse_error(template: &str) -> String {
+        NameTemplate::parse(template).unwrap_err().to_string()
+    }
+
+    fn render(template: &str, ctx: &NamingContext) -> String {
+        NameTemplate::parse(template).unwrap().render(ctx)
+    }
+
+    fn clip() -> NamingContext {
+        NamingContext {
+            original_stem: "clip".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn template_rejects_unbalanced_braces() {
+        assert!(parse_error("{original").contains("position 1 is not closed"));
+        assert!(parse_error("a-{orig{inal}").contains("position 3 is not closed"));
+        assert!(parse_error("original}").contains("unmatched '}' at position 9"));
+        assert!(parse_error("{{original}").contains("unmatched '}' at position 11"));
+        assert_eq!(render("{{{original}}}", &clip()), "{clip}");
+    }
+
+    #[test]
+    fn template_rejects_unknown_placeholders_and_arguments() {
+        assert!(parse_error("{nope}").contains("unknown placeholder {nope}"));
+        assert!(parse_error("{original|shout}").contains("unknown transform '|shout'"));
+        assert!(parse_error("{original:3}").contains("{original} does not take an argument"));
+        assert!(parse_error("{counter:x}").contains("counter padding 'x' is not a number"));
+        assert!(parse_error("{original}/{ext}").contains("not allowed in file names"));
+        assert!(parse_error("").contains("template is empty"));
+    }
+
+    #[test]
+    fn separator_joins_blocks_and_must_be_a_legal_name() {
+        let config = NamingConfig {
+            blocks: vec![
+                NamingBlock::Prefix {
+                    value: "take".to_string(),
+                },
+                NamingBlock::Original,
+            ],
+            separator: " - ".to_string(),
+            ..Default::default()
+        };
+        assert!(validate_naming_config(&config).is_ok());
+        assert_eq!(apply_naming_pipeline(&clip(), &config), "take - clip");
+
+        for separator in ["/", "\\", ":", "\n"] {
+            let config = NamingConfig {
+                separator: separator.to_string(),
+                ..config.clone()
+            };
+            let message = validate_naming_config(&config).unwrap_err().to_string();
+            assert!(message.contains("naming_config.separator"), "{}", message);
+        }
+    }
+
+    #[test]
+    fn case_transforms() {
+        let value = "helloWorld foo-BAR";
+        let cases = [
+            (CaseTransform::Lower, "helloworld foo-bar"),
+            (CaseTransform::Upper, "HELLOWORLD FOO-BAR"),
+            (CaseTransform::Title, "Helloworld Foo-Bar"),
+            (CaseTransform::Kebab, "hello-world-foo-bar"),
+            (CaseTransform::Snake, "hello_world_foo_bar"),
+        ];
+        for (case, expected) in cases {
+            assert_eq!(apply_case(value, case), expected, "{:?}", case);
+        }
+
+        let ctx = NamingContext {
+            original_stem: value.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            render("{original|snake|upper}", &ctx),
+            "HELLO_WORLD_FOO_BAR"
+        );
+    }
+
+    #[test]
+    fn rename_rules_run_in_order_on_the_original_name_only() {
+        let replace = RenameRule::Replace {
+            find: "clip".to_string(),
+            replace: "take".to_string(),
+        };
+        let upper = RenameRule::Case {
+            case: CaseTransform::Upper,
+        };
+        let config = |rules: Vec<RenameRule>| NamingConfig {
+            template: Some("{original}-clip".to_string()),
+            rules,
+            ..Default::default()
+        };
+
+        // Template literals are never rewritten by the rules.
+        assert_eq!(
+            apply_naming_pipeline(&clip(), &config(vec![replace.clone(), upper.clone()])),
+            "TAKE-clip"
+        );
+        assert_eq!(
+            apply_naming_pipeline(&clip(), &config(vec![upper, replace])),
+            "CLIP-clip"
+        );
+    }
+
+    #[test]
+    fn unicode_mode_strips_illegal_characters_and_keeps_letters() {
+        let sanitize = |name| sanitize_filename(name, SanitizeMode::Unicode, true);
+        assert_eq!(sanitize("Café: Live<1>?"), "Café Live1");
+        assert_eq!(sanitize("日本/語\t*|"), "日本語");
+        assert_eq!(sanitize(" Résumé. . "), "Résumé");
+    }
+
+    #[test]
+    fn windows_reserved_names_get_a_suffix() {
+        for (name, mode, preserve_case, expected) in [
+            ("CON", SanitizeMode::Unicode, true, "CON_"),
+            ("Nul", SanitizeMode::Unicode, false, "nul_"),
+            ("com1.backup", SanitizeMode::Unicode, true, "com1.backup_"),
+            ("aux", SanitizeMode::Ascii, true, "aux_"),
+            ("lpt9", SanitizeMode::Transliterate, true, "lpt9_"),
+            ("CONSOLE", SanitizeMode::Unicode, true, "CONSOLE"),
+        ] {
+            assert_eq!(sanitize_filename(name, mode, preserve_case), expected);
+        }
+    }
+
+    #[test]
+    fn transliteration_expands_to_several_letters() {
+        let sanitize = |name, preserve_case| {
+            sanitize_filename(name, SanitizeMode::Transliterate, preserve_case)
+        };
+        assert_eq!(sanitize("Straße Œuvre Æsir", true), "Strasse_Oeuvre_Aesir");
+        assert_eq!(sanitize("Щука ĳs Ψ", true), "Shchuka_ijs_Ps");
+        assert_eq!(sanitize("Щука", false), "shchuka");
+    }
+
+    #[test]
+    fn names_emptied_by_sanitizing_fall_back_to_file() {
+        for (name, mode) in [
+            ("???", SanitizeMode::Ascii),
+            ("日本", SanitizeMode::Ascii),
+            ("日本", SanitizeMode::Transliterate),
+            ("<>. .", SanitizeMode::Unicode),
+        ] {
+            assert_eq!(sanitize_filename(name, mode, true), "file");
+        }
+
+        let ctx = NamingContext {
+            original_stem: "???".to_string(),
+            ..Default::default()
+        };
+        let config = NamingConfig {
+            sanitize_enabled: true,
+            ..Default::default()
+        };
+        assert_eq!(apply_naming_pipeline(&ctx, &config), "file");
     }
 }
@@ -0,0 +1,402 @@
+use crate::error::ConversionError;
+use crate::modules::validation;
+use crate::types::{
+    BackgroundColor, ConversionPreset, ConversionRequest, ExportedPreset, PresetConflict,
+    PresetFile, PresetImportReport, PresetSettings, ResizeConfig, ResizeMode,
+};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+use tokio::sync::Mutex;
+
+const PRESETS_FILE_NAME: &str = "presets.json";
+pub const PRESET_FILE_SCHEMA_VERSION: u32 = 1;
+
+// Serializes read-modify-write cycles on the presets file.
+static PRESETS_LOCK: Mutex<()> = Mutex::const_new(());
+
+pub fn built_in_presets() -> Vec<ConversionPreset> {
+    vec![
+        ConversionPreset {
+            id: "web-1080p-mp4".to_string(),
+            name: "Web 1080p MP4".to_string(),
+            built_in: true,
+            settings: PresetSettings {
+                resize_config: Some(ResizeConfig {
+                    width: 1920,
+                    height: 1080,
+                    mode: ResizeMode::Contain,
+                    background_color: BackgroundColor::Black,
+                }),
+                strip_metadata: true,
+                max_bitrate: Some(8000),
+                video_preset: Some("medium".to_string()),
+                ..base_settings("mp4", 23)
+            },
+        },
+        ConversionPreset {
+            id: "discord-10mb".to_string(),
+            name: "Discord under 10MB".to_string(),
+            built_in: true,
+            settings: PresetSettings {
+                resize_config: Some(ResizeConfig {
+                    width: 1280,
+                    height: 720,
+                    mode: ResizeMode::Contain,
+                    background_color: BackgroundColor::Black,
+                }),
+                strip_metadata: true,
+                target_size_kb: Some(9_500),
+                video_preset: Some("slow".to_string()),
+                ..base_settings("mp4", 28)
+            },
+        },
+        ConversionPreset {
+            id: "podcast-mp3".to_string(),
+            name: "Podcast MP3".to_string(),
+            built_in: true,
+            settings: base_settings("mp3", 5),
+        },
+        ConversionPreset {
+            id: "lossless-archive".to_string(),
+            name: "Lossless archive".to_string(),
+            built_in: true,
+            settings: PresetSettings {
+                video_preset: Some("slow".to_string()),
+                ..base_settings("mkv", 0)
+            },
+        },
+    ]
+}
+
+fn base_settings(output_format: &str, quality_value: u16) -> PresetSettings {
+    PresetSettings {
+        output_format: output_format.to_string(),
+        quality_value,
+        strip_metadata: false,
+        is_muted: false,
+        resize_config: None,
+        naming_config: None,
+        processing_enabled: true,
+        max_bitrate: None,
+        target_size_kb: None,
+        video_preset: None,
+        extra_input_args: Vec::new(),
+        extra_output_args: Vec::new(),
+    }
+}
+
+fn presets_path(app: &AppHandle) -> Result<PathBuf, ConversionError> {
+    let config_dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| ConversionError::IoError {
+            message: format!("Failed to resolve app config dir: {}", e),
+        })?;
+    Ok(config_dir.join(PRESETS_FILE_NAME))
+}
+
+async fn load_user_presets(app: &AppHandle) -> Result<Vec<ConversionPreset>, ConversionError> {
+    let path = presets_path(app)?;
+    let content = match tokio::fs::read_to_string(&path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(ConversionError::from_io(e, &path)),
+    };
+    serde_json::from_str(&content).map_err(|e| ConversionError::InvalidConfig {
+        message: format!("Failed to parse {}: {}", path.to_string_lossy(), e),
+    })
+}
+
+async fn save_user_presets(
+    app: &AppHandle,
+    presets: &[ConversionPreset],
+) -> Result<(), ConversionError> {
+    let path = presets_path(app)?;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| ConversionError::from_io(e, parent))?;
+    }
+    let content =
+        serde_json::to_string_pretty(presets).map_err(|e| ConversionError::InvalidConfig {
+            message: format!("Failed to serialize presets: {}", e),
+        })?;
+    tokio::fs::write(&path, content)
+        .await
+        .map_err(|e| ConversionError::from_io(e, &path))
+}
+
+pub async fn list_presets(app: &AppHandle) -> Result<Vec<ConversionPreset>, ConversionError> {
+    let mut presets = built_in_presets();
+    presets.extend(load_user_presets(app).await?);
+    Ok(presets)
+}
+
+pub async fn get_preset(app: &AppHandle, id: &str) -> Result<ConversionPreset, ConversionError> {
+    list_presets(app)
+        .await?
+        .into_iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| ConversionError::PresetNotFound { id: id.to_string() })
+}
+
+pub async fn create_preset(
+    app: &AppHandle,
+    name: String,
+    settings: PresetSettings,
+) -> Result<ConversionPreset, ConversionError> {
+    validation::validate_preset_settings(&settings)?;
+    let _guard = PRESETS_LOCK.lock().await;
+    let mut presets = load_user_presets(app).await?;
+
+    let preset = ConversionPreset {
+        id: generate_preset_id(),
+        name: validate_name(&name)?,
+        built_in: false,
+        settings,
+    };
+    presets.push(preset.clone());
+    save_user_presets(app, &presets).await?;
+    Ok(preset)
+}
+
+pub async fn update_preset(
+    app: &AppHandle,
+    id: &str,
+    name: String,
+    settings: PresetSettings,
+) -> Result<ConversionPreset, ConversionError> {
+    ensure_not_built_in(id)?;
+    validation::validate_preset_settings(&settings)?;
+    let _guard = PRESETS_LOCK.lock().await;
+    let mut presets = load_user_presets(app).await?;
+
+    let preset = presets
+        .iter_mut()
+        .find(|p| p.id == id)
+        .ok_or_else(|| ConversionError::PresetNotFound { id: id.to_string() })?;
+    preset.name = validate_name(&name)?;
+    preset.settings = settings;
+    let updated = preset.clone();
+
+    save_user_presets(app, &presets).await?;
+    Ok(updated)
+}
+
+pub async fn delete_preset(app: &AppHandle, id: &str) -> Result<(), ConversionError> {
+    ensure_not_built_in(id)?;
+    let _guard = PRESETS_LOCK.lock().await;
+    let mut presets = load_user_presets(app).await?;
+
+    let before = presets.len();
+    presets.retain(|p| p.id != id);
+    if presets.len() == before {
+        return Err(ConversionError::PresetNotFound { id: id.to_string() });
+    }
+    save_user_presets(app, &presets).await
+}
+
+pub async fn resolve_request(
+    app: &AppHandle,
+    mut request: ConversionRequest,
+) -> Result<ConversionRequest, ConversionError> {
+    let Some(preset_id) = request.preset_id.clone() else {
+        return Ok(request);
+    };
+
+    let preset = get_preset(app, &preset_id).await?;
+    preset.settings.apply_to(&mut request);
+    if let Some(overrides) = request.preset_overrides.take() {
+        overrides.apply_to(&mut request);
+    }
+    Ok(request)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PresetFileFormat {
+    Json,
+    Toml,
+}
+
+fn file_format(path: &Path) -> Result<PresetFileFormat, ConversionError> {
+    match path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .as_deref()
+    {
+        Some("json") => Ok(PresetFileFormat::Json),
+        Some("toml") => Ok(PresetFileFormat::Toml),
+        _ => Err(ConversionError::InvalidConfig {
+            message: format!(
+                "path: preset files must end in .json or .toml: {}",
+                path.to_string_lossy()
+            ),
+        }),
+    }
+}
+
+pub async fn export_presets(
+    app: &AppHandle,
+    ids: &[String],
+    path: &Path,
+) -> Result<usize, ConversionError> {
+    let format = file_format(path)?;
+    let all = list_presets(app).await?;
+
+    let mut exported = Vec::with_capacity(ids.len());
+    for id in ids {
+        let preset = all
+            .iter()
+            .find(|p| &p.id == id)
+            .ok_or_else(|| ConversionError::PresetNotFound { id: id.clone() })?;
+        exported.push(ExportedPreset {
+            name: preset.name.clone(),
+            settings: preset.settings.clone(),
+        });
+    }
+
+    let file = PresetFile {
+        schema_version: PRESET_FILE_SCHEMA_VERSION,
+        presets: exported,
+    };
+    let content = match format {
+        PresetFileFormat::Json => serde_json::to_string_pretty(&file).map_err(|e| e.to_string()),
+        PresetFileFormat::Toml => toml::to_string_pretty(&file).map_err(|e| e.to_string()),
+    }
+    .map_err(|e| ConversionError::InvalidConfig {
+        message: format!("Failed to serialize presets: {}", e),
+    })?;
+
+    tokio::fs::write(path, content)
+        .await
+        .map_err(|e| ConversionError::from_io(e, path))?;
+    Ok(file.presets.len())
+}
+
+pub async fn import_presets(
+    app: &AppHandle,
+    path: &Path,
+    replace_existing: bool,
+) -> Result<PresetImportReport, ConversionError> {
+    let format = file_format(path)?;
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| ConversionError::from_io(e, path))?;
+
+    let file: PresetFile = match format {
+        PresetFileFormat::Json => serde_json::from_str(&content).map_err(|e| e.to_string()),
+        PresetFileFormat::Toml => toml::from_str(&content).map_err(|e| e.to_string()),
+    }
+    .map_err(|e| ConversionError::InvalidConfig {
+        message: format!("Failed to parse {}: {}", path.to_string_lossy(), e),
+    })?;
+
+    if file.schema_version == 0 || file.schema_version > PRESET_FILE_SCHEMA_VERSION {
+        return Err(ConversionError::InvalidConfig {
+            message: format!(
+                "schema_version: unsupported preset file version {} (expected {})",
+                file.schema_version, PRESET_FILE_SCHEMA_VERSION
+            ),
+        });
+    }
+
+    let mut seen_names: Vec<String> = Vec::new();
+    for (index, exported) in file.presets.iter().enumerate() {
+        let name = validate_name(&exported.name).map_err(|e| ConversionError::InvalidConfig {
+            message: format!("presets[{}].{}", index, e),
+        })?;
+        validation::validate_preset_settings(&exported.settings).map_err(|e| {
+            ConversionError::InvalidConfig {
+                message: format!("presets[{}] ({}): {}", index, name, e),
+            }
+        })?;
+        let key = name.to_lowercase();
+        if seen_names.contains(&key) {
+            return Err(ConversionError::InvalidConfig {
+                message: format!("presets[{}]: duplicate preset name '{}'", index, name),
+            });
+        }
+        seen_names.push(key);
+    }
+
+    let _guard = PRESETS_LOCK.lock().await;
+    let mut user_presets = load_user_presets(app).await?;
+    let built_ins = built_in_presets();
+    let mut report = PresetImportReport::default();
+
+    for exported in file.presets {
+        let name = exported.name.trim().to_string();
+        let key = name.to_lowercase();
+
+        if let Some(existing) = built_ins.iter().find(|p| p.name.to_lowercase() == key) {
+            report.conflicts.push(PresetConflict {
+                name,
+                existing_id: existing.id.clone(),
+                built_in: true,
+            });
+            continue;
+        }
+
+        match user_presets
+            .iter_mut()
+            .find(|p| p.name.to_lowercase() == key)
+        {
+            Some(existing) if replace_existing => {
+                existing.settings = exported.settings;
+                report.replaced.push(existing.clone());
+            }
+            Some(existing) => {
+                report.conflicts.push(PresetConflict {
+                    name,
+                    existing_id: existing.id.clone(),
+                    built_in: false,
+                });
+            }
+            None => {
+                let preset = ConversionPreset {
+                    id: generate_preset_id(),
+                    name,
+                    built_in: false,
+                    settings: exported.settings,
+                };
+                user_presets.push(preset.clone());
+                report.imported.push(preset);
+            }
+        }
+    }
+
+    if !report.imported.is_empty() || !report.replaced.is_empty() {
+        save_user_presets(app, &user_presets).await?;
+    }
+    Ok(report)
+}
+
+fn ensure_not_built_in(id: &str) -> Result<(), ConversionError> {
+    if built_in_presets().iter().any(|p| p.id == id) {
+        return Err(ConversionError::InvalidConfig {
+            message: format!("Built-in preset '{}' cannot be modified", id),
+        });
+    }
+    Ok(())
+}
+
+fn validate_name(name: &str) -> Result<String, ConversionError> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err(ConversionError::InvalidConfig {
+            message: "name: preset name must not be empty".to_string(),
+        });
+    }
+    Ok(trimmed.to_string())
+}
+
+fn generate_preset_id() -> String {
+    use rand::Rng;
+
+    let suffix: u32 = rand::rng().random();
+    format!(
+        "user-{}-{:08x}",
+        chrono::Local::now().format("%Y%m%d%H%M%S"),
+        suffix
+    )
+}
@@ -0,0 +1,230 @@
+use crate::error::ConversionError;
+use crate::types::{FileInfo, MediaType, ScanRequest};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const DEFAULT_CHUNK_SIZE: usize = 200;
+const MAX_CHUNK_SIZE: usize = 5000;
+
+/// The default filter matches every supported media file that is not hidden.
+#[derive(Debug, Clone, Default)]
+pub struct ScanFilter {
+    media_types: Vec<MediaType>,
+    extensions: Vec<String>,
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+    modified_after: Option<SystemTime>,
+    modified_before: Option<SystemTime>,
+    include_hidden: bool,
+}
+
+impl ScanFilter {
+    pub fn from_request(request: &ScanRequest) -> Result<Self, ConversionError> {
+        if let (Some(min), Some(max)) = (request.min_size, request.max_size) {
+            if min > max {
+                return Err(invalid_field(
+                    "min_size",
+                    format!("{} is larger than max_size {}", min, max),
+                ));
+            }
+        }
+        if let (Some(after), Some(before)) = (request.modified_after, request.modified_before) {
+            if after > before {
+                return Err(invalid_field(
+                    "modified_after",
+                    "is later than modified_before".to_string(),
+                ));
+            }
+        }
+
+        let mut extensions = Vec::with_capacity(request.extensions.len());
+        for ext in &request.extensions {
+            let ext = ext.trim().trim_start_matches('.').to_lowercase();
+            if MediaType::from_extension(&ext).is_none() {
+                return Err(invalid_field(
+                    "extensions",
+                    format!("'{}' is not a supported media extension", ext),
+                ));
+            }
+            extensions.push(ext);
+        }
+
+        let from_millis = |ms: u64| UNIX_EPOCH + Duration::from_millis(ms);
+        Ok(Self {
+            media_types: request.media_types.clone(),
+            extensions,
+            min_size: request.min_size,
+            max_size: request.max_size,
+            modified_after: request.modified_after.map(from_millis),
+            modified_before: request.modified_before.map(from_millis),
+            include_hidden: request.include_hidden,
+        })
+    }
+
+    fn matches(&self, path: &Path, metadata: &fs::Metadata) -> Option<FileInfo> {
+        let extension = path.extension()?.to_string_lossy().to_lowercase();
+        let media_type = MediaType::from_extension(&extension)?;
+
+        if !self.media_types.is_empty() && !self.media_types.contains(&media_type) {
+            return None;
+        }
+        if !self.extensions.is_empty() && !self.extensions.contains(&extension) {
+            return None;
+        }
+
+        let size = metadata.len();
+        if self.min_size.is_some_and(|min| size < min)
+            || self.max_size.is_some_and(|max| size > max)
+        {
+            return None;
+        }
+
+        if self.modified_after.is_some() || self.modified_before.is_some() {
+            let modified = metadata.modified().ok()?;
+            if self.modified_after.is_some_and(|after| modified < after)
+                || self.modified_before.is_some_and(|before| modified > before)
+            {
+                return None;
+            }
+        }
+
+        Some(FileInfo {
+            path: path.to_string_lossy().to_string(),
+            name: path
+                .file_name()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            size,
+            media_type,
+        })
+    }
+}
+
+pub fn chunk_size(requested: Option<usize>) -> usize {
+    requested
+        .unwrap_or(DEFAULT_CHUNK_SIZE)
+        .clamp(1, MAX_CHUNK_SIZE)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ScanOutcome {
+    pub found: usize,
+    pub scanned_files: usize,
+    pub cancelled: bool,
+}
+
+/// Walks `roots` depth-first and hands matching files to `on_chunk` in
+/// batches of `chunk_size`, along with the number of files scanned so far.
+///
+/// Symlinks are followed, but each directory is entered at most once so link
+/// loops end. Unreadable directories are skipped. Blocking; run it off the
+/// async runtime.
+pub fn scan(
+    roots: &[PathBuf],
+    max_depth: Option<usize>,
+    filter: &ScanFilter,
+    cancel: &AtomicBool,
+    chunk_size: usize,
+    mut on_chunk: impl FnMut(Vec<FileInfo>, usize),
+) -> ScanOutcome {
+    let mut outcome = ScanOutcome::default();
+    let mut chunk = Vec::with_capacity(chunk_size);
+    let mut visited: HashSet<PathBuf> = HashSet::new();
+    let mut stack: Vec<(PathBuf, usize)> = Vec::new();
+
+    for root in roots.iter().rev() {
+        match fs::metadata(root) {
+            Ok(metadata) if metadata.is_dir() => stack.push((root.clone(), 0)),
+            Ok(metadata) => {
+                outcome.scanned_files += 1;
+                if let Some(info) = filter.matches(root, &metadata) {
+                    chunk.push(info);
+                    if chunk.len() >= chunk_size {
+                        outcome.found += chunk.len();
+                        on_chunk(std::mem::take(&mut chunk), outcome.scanned_files);
+                    }
+                }
+            }
+            Err(_) => {}
+        }
+    }
+
+    while let Some((dir, depth)) = stack.pop() {
+        if cancel.load(Ordering::Relaxed) {
+            outcome.cancelled = true;
+            break;
+        }
+        let Ok(canonical) = fs::canonicalize(&dir) else {
+            continue;
+        };
+        if !visited.insert(canonical) {
+            continue;
+        }
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+
+        let mut entries: Vec<fs::DirEntry> = entries.filter_map(Result::ok).collect();
+        entries.sort_by_key(|e| e.file_name());
+
+        let mut subdirs = Vec::new();
+        for entry in entries {
+            if !filter.include_hidden && is_hidden(&entry) {
+                continue;
+            }
+            let path = entry.path();
+            // Follows symlinks; broken links are skipped.
+            let Ok(metadata) = fs::metadata(&path) else {
+                continue;
+            };
+
+            if metadata.is_dir() {
+                if max_depth.is_none_or(|max| depth < max) {
+                    subdirs.push((path, depth + 1));
+                }
+                continue;
+            }
+
+            outcome.scanned_files += 1;
+            if let Some(info) = filter.matches(&path, &metadata) {
+                chunk.push(info);
+                if chunk.len() >= chunk_size {
+                    outcome.found += chunk.len();
+                    on_chunk(std::mem::take(&mut chunk), outcome.scanned_files);
+                }
+            }
+        }
+        // Reversed so subdirectories are visited in name order.
+        stack.extend(subdirs.into_iter().rev());
+    }
+
+    if !chunk.is_empty() {
+        outcome.found += chunk.len();
+        on_chunk(chunk, outcome.scanned_files);
+    }
+    outcome
+}
+
+fn is_hidden(entry: &fs::DirEntry) -> bool {
+    if entry.file_name().to_string_lossy().starts_with('.') {
+        return true;
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+        const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+        if let Ok(metadata) = entry.metadata() {
+            return metadata.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0;
+        }
+    }
+    false
+}
+
+fn invalid_field(field: &str, message: String) -> ConversionError {
+    ConversionError::InvalidConfig {
+        message: format!("{}: {}", field, message),
+    }
+}
@@ -0,0 +1,129 @@
+use crate::error::ConversionError;
+use crate::types::{SourceAction, SourceActionReport};
+use std::path::{Path, PathBuf};
+
+/// Applies `action` to the source of a finished conversion. Returns `None`
+/// when the source is left in place.
+///
+/// Nothing is touched when the output is the source file itself, since acting
+/// on the source would then remove the output too.
+pub async fn apply(
+    action: &SourceAction,
+    source: &Path,
+    output: &Path,
+) -> Option<SourceActionReport> {
+    if *action == SourceAction::Leave {
+        return None;
+    }
+
+    let result = if same_file(source, output).await {
+        Err(ConversionError::InvalidConfig {
+            message: "source_action: the output is the source file, so it was left in place"
+                .to_string(),
+        })
+    } else {
+        perform(action, source).await
+    };
+
+    Some(match result {
+        Ok(moved_to) => SourceActionReport {
+            action: action.clone(),
+            success: true,
+            moved_to,
+            error_message: None,
+        },
+        Err(err) => SourceActionReport {
+            action: action.clone(),
+            success: false,
+            moved_to: None,
+            error_message: Some(err.to_string()),
+        },
+    })
+}
+
+async fn perform(action: &SourceAction, source: &Path) -> Result<Option<PathBuf>, ConversionError> {
+    match action {
+        SourceAction::Leave => Ok(None),
+        SourceAction::Delete => tokio::fs::remove_file(source)
+            .await
+            .map(|_| None)
+            .map_err(|e| ConversionError::from_io(e, source)),
+        SourceAction::Trash => {
+            let path = source.to_path_buf();
+            tauri::async_runtime::spawn_blocking(move || trash::delete(&path))
+                .await
+                .map_err(|e| ConversionError::Unknown {
+                    message: format!("Trash task failed: {}", e),
+                })?
+                .map_err(|e| ConversionError::IoError {
+                    message: format!(
+                        "Failed to move {} to the trash: {}",
+                        source.to_string_lossy(),
+                        e
+                    ),
+                })?;
+            Ok(None)
+        }
+        SourceAction::Move { directory } => {
+            let directory = match source.parent() {
+                Some(parent) => parent.join(directory),
+                None => PathBuf::from(directory),
+            };
+            tokio::fs::create_dir_all(&directory)
+                .await
+                .map_err(|e| ConversionError::from_io(e, &directory))?;
+            let target = unused_path(&directory, source).await;
+            match tokio::fs::rename(source, &target).await {
+                Ok(()) => {}
+                // Renames fail across filesystems; fall back to copy and remove.
+                Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+                    tokio::fs::copy(source, &target)
+                        .await
+                        .map_err(|e| ConversionError::from_io(e, &target))?;
+                    tokio::fs::remove_file(source)
+                        .await
+                        .map_err(|e| ConversionError::from_io(e, source))?;
+                }
+                Err(e) => return Err(ConversionError::from_io(e, source)),
+            }
+            Ok(Some(target))
+        }
+    }
+}
+
+async fn same_file(a: &Path, b: &Path) -> bool {
+    match (
+        tokio::fs::canonicalize(a).await,
+        tokio::fs::canonicalize(b).await,
+    ) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+// Picks `directory/name`, adding `_1`, `_2`, ... before the extension when
+// that name is taken.
+async fn unused_path(directory: &Path, source: &Path) -> PathBuf {
+    let file_name = source.file_name().unwrap_or_default();
+    let candidate = directory.join(file_name);
+    if !tokio::fs::try_exists(&candidate).await.unwrap_or(false) {
+        return candidate;
+    }
+
+    let stem = source
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = source
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    let mut counter = 1u32;
+    loop {
+        let candidate = directory.join(format!("{}_{}{}", stem, counter, extension));
+        if !tokio::fs::try_exists(&candidate).await.unwrap_or(false) {
+            return candidate;
+        }
+        counter += 1;
+    }
+}
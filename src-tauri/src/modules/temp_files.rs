@@ -0,0 +1,121 @@
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+const PARTIAL_DIR_NAME: &str = "partial";
+
+// Hidden sibling of the final path, so the rename stays on one filesystem.
+// The real extension stays last so ffmpeg still picks the right muxer.
+pub fn output_path(final_path: &Path, id: &str) -> PathBuf {
+    let stem = final_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "output".to_string());
+    let ext = final_path
+        .extension()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    final_path.with_file_name(format!(".{}.{}.part.{}", stem, safe_id(id), ext))
+}
+
+// Files a job writes while it runs. Their paths are listed under the app data
+// folder until the job ends, so a crash leaves a record for `sweep`, and
+// dropping the guard removes whatever is still there on any exit path.
+pub struct TempFiles {
+    list_path: Option<PathBuf>,
+    paths: Vec<PathBuf>,
+}
+
+impl TempFiles {
+    pub fn new(app: &AppHandle, id: &str) -> Self {
+        Self {
+            list_path: partial_dir(app).map(|dir| dir.join(format!("{}.txt", safe_id(id)))),
+            paths: Vec::new(),
+        }
+    }
+
+    pub fn track(&mut self, path: PathBuf) {
+        self.paths.push(path);
+        let Some(list_path) = &self.list_path else {
+            return;
+        };
+        let contents = self
+            .paths
+            .iter()
+            .map(|p| p.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("\n");
+        if let Some(dir) = list_path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let _ = std::fs::write(list_path, contents);
+    }
+}
+
+impl Drop for TempFiles {
+    fn drop(&mut self) {
+        for path in &self.paths {
+            let _ = std::fs::remove_file(path);
+        }
+        if let Some(list_path) = &self.list_path {
+            let _ = std::fs::remove_file(list_path);
+        }
+    }
+}
+
+/// Removes the temp files of jobs that never finished, e.g. because the app
+/// crashed. Must run before any job starts.
+pub fn sweep(app: &AppHandle) {
+    let Some(dir) = partial_dir(app) else {
+        return;
+    };
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if let Ok(contents) = std::fs::read_to_string(entry.path()) {
+            for line in contents.lines().filter(|l| !l.is_empty()) {
+                let _ = std::fs::remove_file(line);
+            }
+        }
+        let _ = std::fs::remove_file(entry.path());
+    }
+}
+
+#[cfg(windows)]
+pub fn set_hidden(path: &Path, hidden: bool) -> bool {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::{
+        SetFileAttributesW, FILE_ATTRIBUTE_HIDDEN, FILE_ATTRIBUTE_NORMAL,
+    };
+
+    let wide: Vec<u16> = path
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+    let attributes = if hidden {
+        FILE_ATTRIBUTE_HIDDEN
+    } else {
+        FILE_ATTRIBUTE_NORMAL
+    };
+    unsafe { SetFileAttributesW(wide.as_ptr(), attributes) != 0 }
+}
+
+// The leading dot already hides the file everywhere but Windows.
+#[cfg(not(windows))]
+pub fn set_hidden(_path: &Path, _hidden: bool) -> bool {
+    true
+}
+
+fn partial_dir(app: &AppHandle) -> Option<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .ok()
+        .map(|dir| dir.join(PARTIAL_DIR_NAME))
+}
+
+fn safe_id(id: &str) -> String {
+    id.chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect()
+}
@@ -0,0 +1,579 @@
+use crate::error::ConversionError;
+use crate::modules::naming;
+use crate::types::{
+    parse_output_format, AudioFormat, ChapterMode, ConversionRequest, CoverArtSource, ImageFormat,
+    MediaType, NamingConfig, OutputFormat, PresetSettings, QualitySetting, ResizeConfig,
+    SourceAction, VideoFormat, VideoPreset,
+};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+const MAX_RESIZE_DIMENSION: u32 = 16384;
+
+#[derive(Debug, Clone)]
+pub struct ValidatedSettings {
+    pub output_format: OutputFormat,
+    pub quality: QualitySetting,
+    pub video_preset: VideoPreset,
+    pub conflict_suffix: String,
+}
+
+const DEFAULT_CONFLICT_SUFFIX: &str = "_{n}";
+
+// Borrowed view over the fields shared by requests and presets.
+struct SettingsRef<'a> {
+    output_format: &'a str,
+    quality_value: u16,
+    resize_config: Option<&'a ResizeConfig>,
+    naming_config: Option<&'a NamingConfig>,
+    max_bitrate: Option<u64>,
+    target_size_kb: Option<u64>,
+    video_preset: Option<&'a str>,
+    extra_input_args: &'a [String],
+    extra_output_args: &'a [String],
+}
+
+pub fn validate_request(request: &ConversionRequest) -> Result<ValidatedSettings, ConversionError> {
+    let conflict_suffix = validate_conflict_suffix(request.conflict_suffix.as_deref())?;
+    validate_metadata_keys(&request.set_metadata)?;
+    // ffmpeg's image muxers write no container tags, so a whitelist or custom
+    // tags would be silently dropped.
+    if matches!(
+        parse_output_format(&request.output_format),
+        Some(OutputFormat::Image(_))
+    ) {
+        if request.keep_metadata.is_some() {
+            return Err(invalid_field(
+                "keep_metadata",
+                format!("tags cannot be kept in {} outputs", request.output_format),
+            ));
+        }
+        if !request.set_metadata.is_empty() {
+            return Err(invalid_field(
+                "set_metadata",
+                format!(
+                    "tags cannot be written to {} outputs",
+                    request.output_format
+                ),
+            ));
+        }
+    }
+    if let Some(cover) = &request.cover_art {
+        validate_cover_art(cover, &request.output_format)?;
+    }
+    if let ChapterMode::Import { .. } = request.chapters {
+        let supports_chapters = matches!(
+            parse_output_format(&request.output_format),
+            Some(
+                OutputFormat::Video(VideoFormat::Mkv | VideoFormat::Mp4)
+                    | OutputFormat::Audio(AudioFormat::M4a)
+            )
+        );
+        if !supports_chapters {
+            return Err(invalid_field(
+                "chapters",
+                format!(
+                    "chapters cannot be imported into {} outputs",
+                    request.output_format
+                ),
+            ));
+        }
+    }
+    if request.preserve_structure
+        && request.output_directory.is_some()
+        && request.base_directory.as_deref().is_none_or(str::is_empty)
+    {
+        return Err(invalid_field(
+            "base_directory",
+            "required when preserve_structure is set".to_string(),
+        ));
+    }
+    if let SourceAction::Move { directory } = &request.source_action {
+        if directory.trim().is_empty() {
+            return Err(invalid_field(
+                "source_action.directory",
+                "must not be empty".to_string(),
+            ));
+        }
+    }
+    if let Some(oversize) = &request.oversize_config {
+        if !oversize.threshold_percent.is_finite() || oversize.threshold_percent < 0.0 {
+            return Err(invalid_field(
+                "oversize_config.threshold_percent",
+                format!("{} must be 0 or greater", oversize.threshold_percent),
+            ));
+        }
+    }
+    let settings = validate_settings(&SettingsRef {
+        output_format: &request.output_format,
+        quality_value: request.quality_value,
+        resize_config: request.resize_config.as_ref(),
+        naming_config: request.naming_config.as_ref(),
+        max_bitrate: request.max_bitrate,
+        target_size_kb: request.target_size_kb,
+        video_preset: request.video_preset.as_deref(),
+        extra_input_args: &request.extra_input_args,
+        extra_output_args: &request.extra_output_args,
+    })?;
+    Ok(ValidatedSettings {
+        conflict_suffix,
+        ..settings
+    })
+}
+
+pub fn validate_preset_settings(
+    settings: &PresetSettings,
+) -> Result<ValidatedSettings, ConversionError> {
+    validate_settings(&SettingsRef {
+        output_format: &settings.output_format,
+        quality_value: settings.quality_value,
+        resize_config: settings.resize_config.as_ref(),
+        naming_config: settings.naming_config.as_ref(),
+        max_bitrate: settings.max_bitrate,
+        target_size_kb: settings.target_size_kb,
+        video_preset: settings.video_preset.as_deref(),
+        extra_input_args: &settings.extra_input_args,
+        extra_output_args: &settings.extra_output_args,
+    })
+}
+
+fn validate_settings(settings: &SettingsRef) -> Result<ValidatedSettings, ConversionError> {
+    let output_format = parse_output_format(settings.output_format).ok_or_else(|| {
+        ConversionError::UnsupportedFormat {
+            input: String::new(),
+            output: settings.output_format.to_string(),
+            reason: "Unknown output format".to_string(),
+        }
+    })?;
+
+    let quality = validate_quality(&output_format, settings.quality_value)?;
+
+    if let Some(naming_config) = settings.naming_config {
+        naming::validate_naming_config(naming_config)?;
+    }
+
+    let video_preset = match settings.video_preset {
+        Some(value) => VideoPreset::parse(value)
+            .ok_or_else(|| invalid_field("video_preset", format!("unknown preset '{}'", value)))?,
+        None => VideoPreset::Medium,
+    };
+
+    if let Some(resize) = settings.resize_config {
+        for (field, value) in [
+            ("resize_config.width", resize.width),
+            ("resize_config.height", resize.height),
+        ] {
+            if !(2..=MAX_RESIZE_DIMENSION).contains(&value) {
+                return Err(invalid_field(
+                    field,
+                    format!("{} is out of range 2-{}", value, MAX_RESIZE_DIMENSION),
+                ));
+            }
+        }
+    }
+
+    if settings.max_bitrate == Some(0) {
+        return Err(invalid_field(
+            "max_bitrate",
+            "must be greater than 0".to_string(),
+        ));
+    }
+    if settings.target_size_kb == Some(0) {
+        return Err(invalid_field(
+            "target_size_kb",
+            "must be greater than 0".to_string(),
+        ));
+    }
+
+    validate_extra_args("extra_input_args", settings.extra_input_args)?;
+    validate_extra_args("extra_output_args", settings.extra_output_args)?;
+
+    Ok(ValidatedSettings {
+        output_format,
+        quality,
+        video_preset,
+        conflict_suffix: DEFAULT_CONFLICT_SUFFIX.to_string(),
+    })
+}
+
+fn validate_quality(
+    output_format: &OutputFormat,
+    value: u16,
+) -> Result<QualitySetting, ConversionError> {
+    let (min, max): (u16, u16) = match output_format {
+        OutputFormat::Video(VideoFormat::Mp4 | VideoFormat::Mkv | VideoFormat::Mov) => (0, 51),
+        OutputFormat::Video(VideoFormat::Webm) => (0, 63),
+        OutputFormat::Image(ImageFormat::Jpg) => (2, 31),
+        OutputFormat::Image(ImageFormat::Webp) => (0, 100),
+        OutputFormat::Image(ImageFormat::Png) => return Ok(QualitySetting::Lossless),
+        OutputFormat::Audio(AudioFormat::Mp3) => (0, 9),
+        OutputFormat::Audio(AudioFormat::Aac | AudioFormat::M4a) => (128, 320),
+        OutputFormat::Audio(AudioFormat::Ogg) => (1, 8),
+        OutputFormat::Audio(AudioFormat::Flac) => return Ok(QualitySetting::Lossless),
+    };
+
+    if !(min..=max).contains(&value) {
+        return Err(invalid_field(
+            "quality_value",
+            format!(
+                "{} is out of range {}-{} for {}",
+                value,
+                min,
+                max,
+                output_format.extension()
+            ),
+        ));
+    }
+
+    Ok(match output_format {
+        OutputFormat::Video(_) => QualitySetting::Crf(value as u8),
+        OutputFormat::Image(ImageFormat::Jpg) => QualitySetting::ImageQscale(value as u8),
+        OutputFormat::Image(_) => QualitySetting::WebpQuality(value as u8),
+        OutputFormat::Audio(AudioFormat::Aac | AudioFormat::M4a) => {
+            QualitySetting::AudioBitrateKbps(value)
+        }
+        OutputFormat::Audio(_) => QualitySetting::AudioVbr(value as u8),
+    })
+}
+
+fn validate_conflict_suffix(value: Option<&str>) -> Result<String, ConversionError> {
+    let Some(pattern) = value else {
+        return Ok(DEFAULT_CONFLICT_SUFFIX.to_string());
+    };
+    if !pattern.contains("{n}") {
+        return Err(invalid_field(
+            "conflict_suffix",
+            format!("'{}' must contain the {{n}} placeholder", pattern),
+        ));
+    }
+    if pattern.contains(|c: char| "/\\:*?\"<>|".contains(c) || c.is_control()) {
+        return Err(invalid_field(
+            "conflict_suffix",
+            format!(
+                "'{}' contains characters not allowed in file names",
+                pattern
+            ),
+        ));
+    }
+    Ok(pattern.to_string())
+}
+
+fn validate_metadata_keys(tags: &BTreeMap<String, String>) -> Result<(), ConversionError> {
+    for key in tags.keys() {
+        let valid = !key.is_empty()
+            && key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "_-.:".contains(c));
+        if !valid {
+            return Err(invalid_field(
+                "set_metadata",
+                format!("'{}' is not a valid tag name", key),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn validate_cover_art(cover: &CoverArtSource, output_format: &str) -> Result<(), ConversionError> {
+    // Raw AAC has no container to hold a picture, and Ogg stores pictures in
+    // a comment block rather than the attached picture stream used here.
+    if !matches!(
+        parse_output_format(output_format),
+        Some(OutputFormat::Audio(
+            AudioFormat::Mp3 | AudioFormat::M4a | AudioFormat::Flac
+        ))
+    ) {
+        return Err(invalid_field(
+            "cover_art",
+            format!("cover art cannot be embedded in {} outputs", output_format),
+        ));
+    }
+
+    match cover {
+        CoverArtSource::Image { path } => {
+            let is_image = Path::new(path)
+                .extension()
+                .and_then(|e| MediaType::from_extension(&e.to_string_lossy()))
+                == Some(MediaType::Image);
+            if !is_image {
+                return Err(invalid_field(
+                    "cover_art.path",
+                    format!("'{}' is not a supported image", path),
+                ));
+            }
+        }
+        CoverArtSource::VideoFrame { time_secs } => {
+            if let Some(t) = time_secs {
+                if !t.is_finite() || *t < 0.0 {
+                    return Err(invalid_field(
+                        "cover_art.time_secs",
+                        format!("{} must be 0 or greater", t),
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// Options that would break progress parsing, output path handling or the
+// single-output assumption if a user slipped them into the extra arguments.
+const DENIED_EXTRA_OPTIONS: &[&str] = &[
+    "-i",
+    "-y",
+    "-n",
+    "-progress",
+    "-stats",
+    "-nostats",
+    "-stats_period",
+    "-loglevel",
+    "-v",
+    "-report",
+    "-f",
+    "-filter_script",
+    "-filter_complex_script",
+    "-attach",
+    "-dump_attachment",
+    "-dump",
+    "-vstats",
+    "-vstats_file",
+    "-passlogfile",
+];
+
+// Options that consume the following argument. Anything else is treated as a
+// flag, so a bare token after it would become an extra output.
+const VALUE_EXTRA_OPTIONS: &[&str] = &[
+    "-c",
+    "-codec",
+    "-vcodec",
+    "-acodec",
+    "-scodec",
+    "-b",
+    "-vb",
+    "-ab",
+    "-maxrate",
+    "-minrate",
+    "-bufsize",
+    "-q",
+    "-qscale",
+    "-aq",
+    "-crf",
+    "-cq",
+    "-qp",
+    "-qmin",
+    "-qmax",
+    "-rc",
+    "-preset",
+    "-tune",
+    "-profile",
+    "-level",
+    "-x264-params",
+    "-x264opts",
+    "-x265-params",
+    "-svtav1-params",
+    "-cpu-used",
+    "-deadline",
+    "-speed",
+    "-row-mt",
+    "-tile-columns",
+    "-lag-in-frames",
+    "-auto-alt-ref",
+    "-g",
+    "-keyint_min",
+    "-bf",
+    "-refs",
+    "-sc_threshold",
+    "-threads",
+    "-pix_fmt",
+    "-pixel_format",
+    "-sample_fmt",
+    "-r",
+    "-framerate",
+    "-fps_mode",
+    "-vsync",
+    "-s",
+    "-video_size",
+    "-aspect",
+    "-ar",
+    "-ac",
+    "-channel_layout",
+    "-vf",
+    "-af",
+    "-filter",
+    "-filter_complex",
+    "-lavfi",
+    "-bsf",
+    "-map",
+    "-map_metadata",
+    "-map_chapters",
+    "-metadata",
+    "-disposition",
+    "-tag",
+    "-vtag",
+    "-atag",
+    "-ss",
+    "-sseof",
+    "-t",
+    "-to",
+    "-itsoffset",
+    "-fs",
+    "-frames",
+    "-vframes",
+    "-aframes",
+    "-timecode",
+    "-movflags",
+    "-brand",
+    "-fflags",
+    "-flags",
+    "-strict",
+    "-avoid_negative_ts",
+    "-max_muxing_queue_size",
+    "-max_interleave_delta",
+    "-muxdelay",
+    "-muxpreload",
+    "-analyzeduration",
+    "-probesize",
+    "-hwaccel",
+    "-hwaccel_device",
+    "-hwaccel_output_format",
+    "-color_primaries",
+    "-color_trc",
+    "-colorspace",
+    "-color_range",
+    "-id3v2_version",
+    "-write_id3v1",
+    "-compression_level",
+    "-quality",
+    "-lossless",
+    "-loop",
+];
+
+const FILTER_EXTRA_OPTIONS: &[&str] = &["-vf", "-af", "-filter", "-filter_complex", "-lavfi"];
+
+// Filters that open a file or URL of their own, adding an input the job
+// never validated.
+const DENIED_FILTERS: &[&str] = &["movie", "amovie", "sendcmd", "asendcmd"];
+
+pub fn validate_extra_args(field: &str, args: &[String]) -> Result<(), ConversionError> {
+    let mut value_for: Option<&str> = None;
+
+    for arg in args {
+        let is_option = arg.len() > 1
+            && arg.starts_with('-')
+            && arg[1..].starts_with(|c: char| c.is_ascii_alphabetic());
+
+        if is_option {
+            let name = arg.split(':').next().unwrap_or(arg);
+            if DENIED_EXTRA_OPTIONS.contains(&name) {
+                return Err(invalid_field(
+                    field,
+                    format!("option '{}' is not allowed", arg),
+                ));
+            }
+            value_for = VALUE_EXTRA_OPTIONS.contains(&name).then_some(name);
+        } else {
+            let option = match value_for.take() {
+                Some(option) if arg != "-" && !arg.starts_with("pipe:") => option,
+                _ => {
+                    return Err(invalid_field(
+                        field,
+                        format!("'{}' would be treated as an extra output", arg),
+                    ))
+                }
+            };
+            if FILTER_EXTRA_OPTIONS.contains(&option) {
+                if let Some(filter) = filter_names(arg).find(|f| DENIED_FILTERS.contains(f)) {
+                    return Err(invalid_field(
+                        field,
+                        format!("filter '{}' is not allowed", filter),
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Filter names in a graph such as `[0:v]scale=640:-2[a];[a]hflip`. Quoted
+// arguments are not parsed, so a comma inside one can only add false matches.
+fn filter_names(graph: &str) -> impl Iterator<Item = &str> {
+    graph.split([',', ';']).map(|filter| {
+        let mut filter = filter.trim_start();
+        while let Some(rest) = filter.strip_prefix('[') {
+            filter = rest
+                .split_once(']')
+                .map_or("", |(_, rest)| rest)
+                .trim_start();
+        }
+        filter
+            .split(['=', '@', '['])
+            .next()
+            .unwrap_or_default()
+            .trim()
+    })
+}
+
+fn invalid_field(field: &str, message: String) -> ConversionError {
+    ConversionError::InvalidConfig {
+        message: format!("{}: {}", field, message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(args: &[&str]) -> Result<(), String> {
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        validate_extra_args("extra_args", &args).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn accepts_options_with_values() {
+        assert!(check(&["-c:v", "libx264", "-preset", "slow", "-an"]).is_ok());
+        assert!(check(&["-vf", "scale=-2:720", "-metadata", "title=x"]).is_ok());
+        assert!(check(&["-filter_complex", "[0:v]split[a][b];[a][b]hstack"]).is_ok());
+    }
+
+    #[test]
+    fn rejects_input_and_overwrite_options() {
+        for args in [
+            &["-i", "other.mp4"][..],
+            &["-y"],
+            &["-c:a", "aac", "-i:0", "x.wav"],
+        ] {
+            let message = check(args).unwrap_err();
+            assert!(message.contains("is not allowed"), "{}", message);
+        }
+    }
+
+    #[test]
+    fn rejects_output_paths_after_a_value_option() {
+        for args in [
+            &["-c:v", "libx264", "out.mp4"][..],
+            &["-metadata", "title=x", "/tmp/evil.mp4"],
+            &["-an", "evil.mp4"],
+            &["-c", "-"],
+            &["-f:a", "pipe:1"],
+            &["-/vf", "graph.txt"],
+        ] {
+            assert!(check(args).is_err(), "{:?}", args);
+        }
+        let message = check(&["-b:v", "2M", "out.mp4"]).unwrap_err();
+        assert!(message.contains("'out.mp4' would be treated as an extra output"));
+    }
+
+    #[test]
+    fn rejects_filters_that_read_files() {
+        for args in [
+            &["-filter_complex", "movie=/etc/passwd[wm];[0:v][wm]overlay"][..],
+            &["-filter_complex:v", "[0:v] [in]amovie=a.wav [x]"],
+            &["-vf", "scale=640:-2,sendcmd=f=cmds.txt"],
+            &["-lavfi", "amovie@src=x.mp3"],
+        ] {
+            let message = check(args).unwrap_err();
+            assert!(message.contains("filter '"), "{}", message);
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use crate::modules::ffmpeg;
+use crate::types::{
+    ConversionConfig, OutputFormat, VerificationCheck, VerificationCheckKind, VerificationReport,
+};
+use regex::Regex;
+use std::sync::OnceLock;
+
+const DURATION_TOLERANCE_SECS: f64 = 0.5;
+const DURATION_TOLERANCE_RATIO: f64 = 0.01;
+const MAX_REPORTED_DECODE_ERRORS: usize = 3;
+
+#[derive(Debug, Clone, Default)]
+pub struct MediaProbe {
+    pub duration_secs: Option<f64>,
+    pub video_streams: usize,
+    pub audio_streams: usize,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+fn stream_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"^\s*Stream #\d+:\d+.*?: (Video|Audio): (.*)$")
+            .expect("Failed to compile stream regex")
+    })
+}
+
+fn dimensions_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"\b(\d{2,5})x(\d{2,5})\b").expect("Failed to compile dimensions regex")
+    })
+}
+
+// Parses the stream summary ffmpeg prints for `-i <file>` without an output.
+pub fn parse_probe(log: &str) -> Option<MediaProbe> {
+    if !log.lines().any(|l| l.starts_with("Input #0")) {
+        return None;
+    }
+
+    let mut probe = MediaProbe::default();
+    for line in log.lines() {
+        if probe.duration_secs.is_none() {
+            probe.duration_secs = ffmpeg::parse_duration(line);
+        }
+
+        let Some(caps) = stream_regex().captures(line) else {
+            continue;
+        };
+        let details = &caps[2];
+        if &caps[1] == "Audio" {
+            probe.audio_streams += 1;
+            continue;
+        }
+        // Cover art in audio files shows up as a video stream.
+        if details.contains("(attached pic)") {
+            continue;
+        }
+        probe.video_streams += 1;
+        if probe.width.is_none() {
+            if let Some(dims) = dimensions_regex().captures(details) {
+                probe.width = dims[1].parse().ok();
+                probe.height = dims[2].parse().ok();
+            }
+        }
+    }
+
+    Some(probe)
+}
+
+pub fn verify(
+    config: &ConversionConfig,
+    input_log: &str,
+    output_log: &str,
+    decode_exit_code: Option<i32>,
+    decode_log: &str,
+) -> VerificationReport {
+    let input = parse_probe(input_log).unwrap_or_default();
+    let mut checks = Vec::new();
+
+    match parse_probe(output_log) {
+        Some(output) => {
+            checks.push(check_streams(config, &input, &output));
+            if let Some(check) = check_duration(config, &input, &output) {
+                checks.push(check);
+            }
+        }
+        None => checks.push(VerificationCheck {
+            kind: VerificationCheckKind::Streams,
+            passed: false,
+            message: "output could not be probed".to_string(),
+        }),
+    }
+
+    checks.push(check_decode(decode_exit_code, decode_log));
+
+    VerificationReport {
+        passed: checks.iter().all(|c| c.passed),
+        checks,
+    }
+}
+
+fn check_streams(
+    config: &ConversionConfig,
+    input: &MediaProbe,
+    output: &MediaProbe,
+) -> VerificationCheck {
+    let (want_video, want_audio) = match config.output_format {
+        OutputFormat::Video(_) => (
+            input.video_streams > 0,
+            input.audio_streams > 0 && !config.is_muted,
+        ),
+        OutputFormat::Audio(_) => (false, true),
+        OutputFormat::Image(_) => (true, false),
+    };
+
+    let mut missing = Vec::new();
+    if want_video && output.video_streams == 0 {
+        missing.push("video");
+    }
+    if want_audio && output.audio_streams == 0 {
+        missing.push("audio");
+    }
+
+    VerificationCheck {
+        kind: VerificationCheckKind::Streams,
+        passed: missing.is_empty(),
+        message: if missing.is_empty() {
+            format!(
+                "{} video, {} audio stream(s)",
+                output.video_streams, output.audio_streams
+            )
+        } else {
+            format!("missing {} stream", missing.join(" and "))
+        },
+    }
+}
+
+fn check_duration(
+    config: &ConversionConfig,
+    input: &MediaProbe,
+    output: &MediaProbe,
+) -> Option<VerificationCheck> {
+    if matches!(config.output_format, OutputFormat::Image(_)) {
+        return None;
+    }
+    // Requests have no trim range yet, so the whole input is the expected
+    // length.
+    let expected = input.duration_secs.filter(|d| *d > 0.0)?;
+
+    let Some(actual) = output.duration_secs else {
+        return Some(VerificationCheck {
+            kind: VerificationCheckKind::Duration,
+            passed: false,
+            message: "output has no duration".to_string(),
+        });
+    };
+
+    let tolerance = DURATION_TOLERANCE_SECS.max(expected * DURATION_TOLERANCE_RATIO);
+    Some(VerificationCheck {
+        kind: VerificationCheckKind::Duration,
+        passed: (actual - expected).abs() <= tolerance,
+        message: format!(
+            "duration {:.2}s, expected {:.2}s (±{:.2}s)",
+            actual, expected, tolerance
+        ),
+    })
+}
+
+fn check_decode(exit_code: Option<i32>, log: &str) -> VerificationCheck {
+    let errors: Vec<&str> = log.lines().filter(|l| !l.trim().is_empty()).collect();
+    let passed = exit_code == Some(0) && errors.is_empty();
+
+    VerificationCheck {
+        kind: VerificationCheckKind::Decode,
+        passed,
+        message: if passed {
+            "decoded without errors".to_string()
+        } else if errors.is_empty() {
+            format!("decoder exited with {:?}", exit_code)
+        } else {
+            format!(
+                "decode errors: {}",
+                errors
+                    .iter()
+                    .take(MAX_REPORTED_DECODE_ERRORS)
+                    .copied()
+                    .collect::<Vec<_>>()
+                    .join(" | ")
+            )
+        },
+    }
+}
@@ -0,0 +1,515 @@
+use crate::commands;
+use crate::error::ConversionError;
+use crate::modules::{presets, scanner};
+use crate::state::AppState;
+use crate::types::{
+    ConversionRequest, FileInfo, SourceAction, WatchEvent, WatchEventKind, WatchFolder,
+    WatchFolderSettings,
+};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::{mpsc, Mutex};
+
+const WATCH_FOLDERS_FILE_NAME: &str = "watch_folders.json";
+const HANDLED_FILES_FILE_NAME: &str = "watch_handled.json";
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+const MAX_SETTLE_SECS: u64 = 3600;
+
+// In-memory copy of the watch folders file, loaded on first use. Holding the
+// lock also serializes read-modify-write cycles on the file.
+static WATCH_FOLDERS: Mutex<Option<Vec<WatchFolder>>> = Mutex::const_new(None);
+
+fn watch_folders_path(app: &AppHandle) -> Result<PathBuf, ConversionError> {
+    let config_dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| ConversionError::IoError {
+            message: format!("Failed to resolve app config dir: {}", e),
+        })?;
+    Ok(config_dir.join(WATCH_FOLDERS_FILE_NAME))
+}
+
+async fn load_watch_folders(app: &AppHandle) -> Result<Vec<WatchFolder>, ConversionError> {
+    let path = watch_folders_path(app)?;
+    let content = match tokio::fs::read_to_string(&path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(ConversionError::from_io(e, &path)),
+    };
+    serde_json::from_str(&content).map_err(|e| ConversionError::InvalidConfig {
+        message: format!("Failed to parse {}: {}", path.to_string_lossy(), e),
+    })
+}
+
+async fn save_watch_folders(
+    app: &AppHandle,
+    folders: &[WatchFolder],
+) -> Result<(), ConversionError> {
+    let path = watch_folders_path(app)?;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| ConversionError::from_io(e, parent))?;
+    }
+    let content =
+        serde_json::to_string_pretty(folders).map_err(|e| ConversionError::InvalidConfig {
+            message: format!("Failed to serialize watch folders: {}", e),
+        })?;
+    tokio::fs::write(&path, content)
+        .await
+        .map_err(|e| ConversionError::from_io(e, &path))
+}
+
+async fn cached<'a>(
+    app: &AppHandle,
+    cache: &'a mut Option<Vec<WatchFolder>>,
+) -> Result<&'a mut Vec<WatchFolder>, ConversionError> {
+    if cache.is_none() {
+        *cache = Some(load_watch_folders(app).await?);
+    }
+    Ok(cache.get_or_insert_with(Vec::new))
+}
+
+pub async fn list_watch_folders(app: &AppHandle) -> Result<Vec<WatchFolder>, ConversionError> {
+    let mut cache = WATCH_FOLDERS.lock().await;
+    Ok(cached(app, &mut cache).await?.clone())
+}
+
+pub async fn create_watch_folder(
+    app: &AppHandle,
+    settings: WatchFolderSettings,
+) -> Result<WatchFolder, ConversionError> {
+    validate_settings(app, &settings).await?;
+    let mut cache = WATCH_FOLDERS.lock().await;
+    let mut folders = cached(app, &mut cache).await?.clone();
+
+    let folder = WatchFolder {
+        id: generate_watch_id(),
+        settings,
+    };
+    folders.push(folder.clone());
+    save_watch_folders(app, &folders).await?;
+    *cache = Some(folders);
+    Ok(folder)
+}
+
+pub async fn update_watch_folder(
+    app: &AppHandle,
+    id: &str,
+    settings: WatchFolderSettings,
+) -> Result<WatchFolder, ConversionError> {
+    validate_settings(app, &settings).await?;
+    let mut cache = WATCH_FOLDERS.lock().await;
+    let mut folders = cached(app, &mut cache).await?.clone();
+
+    let folder = folders
+        .iter_mut()
+        .find(|f| f.id == id)
+        .ok_or_else(|| ConversionError::WatchFolderNotFound { id: id.to_string() })?;
+    folder.settings = settings;
+    let updated = folder.clone();
+
+    save_watch_folders(app, &folders).await?;
+    *cache = Some(folders);
+    Ok(updated)
+}
+
+pub async fn delete_watch_folder(app: &AppHandle, id: &str) -> Result<(), ConversionError> {
+    let mut cache = WATCH_FOLDERS.lock().await;
+    let mut folders = cached(app, &mut cache).await?.clone();
+
+    let before = folders.len();
+    folders.retain(|f| f.id != id);
+    if folders.len() == before {
+        return Err(ConversionError::WatchFolderNotFound { id: id.to_string() });
+    }
+    save_watch_folders(app, &folders).await?;
+    *cache = Some(folders);
+    Ok(())
+}
+
+async fn validate_settings(
+    app: &AppHandle,
+    settings: &WatchFolderSettings,
+) -> Result<(), ConversionError> {
+    let watched = Path::new(&settings.path);
+    if !watched.is_absolute() {
+        return Err(invalid_field(
+            "path",
+            "must be an absolute path".to_string(),
+        ));
+    }
+    if !tokio::fs::metadata(watched).await.is_ok_and(|m| m.is_dir()) {
+        return Err(invalid_field(
+            "path",
+            format!("{} is not a directory", settings.path),
+        ));
+    }
+
+    presets::get_preset(app, &settings.preset_id).await?;
+
+    validate_target_directory("output_directory", &settings.output_directory, settings)?;
+    if let SourceAction::Move { directory } = &settings.source_action {
+        validate_target_directory("source_action.directory", directory, settings)?;
+    }
+
+    if !(1..=MAX_SETTLE_SECS).contains(&settings.settle_secs) {
+        return Err(invalid_field(
+            "settle_secs",
+            format!("must be between 1 and {}", MAX_SETTLE_SECS),
+        ));
+    }
+    Ok(())
+}
+
+// Files written where the watcher looks would be picked up again as new input.
+fn validate_target_directory(
+    field: &str,
+    directory: &str,
+    settings: &WatchFolderSettings,
+) -> Result<(), ConversionError> {
+    let target = Path::new(directory);
+    if directory.trim().is_empty() || !target.is_absolute() {
+        return Err(invalid_field(field, "must be an absolute path".to_string()));
+    }
+    let watched = Path::new(&settings.path);
+    let overlaps = if settings.recursive {
+        target.starts_with(watched)
+    } else {
+        target == watched
+    };
+    if overlaps {
+        return Err(invalid_field(
+            field,
+            "must not be inside the watched folder".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn generate_watch_id() -> String {
+    use rand::Rng;
+
+    let suffix: u32 = rand::rng().random();
+    format!(
+        "watch-{}-{:08x}",
+        chrono::Local::now().format("%Y%m%d%H%M%S"),
+        suffix
+    )
+}
+
+struct WatchJob {
+    folder: WatchFolder,
+    job_id: String,
+    path: PathBuf,
+    size: u64,
+}
+
+// Sent back to the poller when a job ends.
+struct JobOutcome {
+    watch_id: String,
+    path: PathBuf,
+    size: u64,
+    handled: bool,
+}
+
+/// Starts polling the enabled watch folders in the background.
+///
+/// Files that have not been handled yet, including ones that were already
+/// there, are converted one at a time once their size has stopped changing
+/// for the folder's settle time. Handled files are remembered across
+/// restarts; failed ones are retried once they change.
+pub fn start(app: AppHandle) {
+    let (job_sender, job_receiver) = mpsc::unbounded_channel();
+    let (outcome_sender, outcome_receiver) = mpsc::unbounded_channel();
+    tauri::async_runtime::spawn(run_jobs(app.clone(), job_receiver, outcome_sender));
+    tauri::async_runtime::spawn(poll(app, job_sender, outcome_receiver));
+}
+
+// Converted or skipped files per watch folder, kept so a restart doesn't
+// convert them again.
+type HandledFiles = HashMap<String, HashSet<PathBuf>>;
+
+fn handled_files_path(app: &AppHandle) -> Result<PathBuf, ConversionError> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| ConversionError::IoError {
+            message: format!("Failed to resolve app data dir: {}", e),
+        })?;
+    Ok(data_dir.join(HANDLED_FILES_FILE_NAME))
+}
+
+async fn load_handled_files(app: &AppHandle) -> HandledFiles {
+    let Ok(path) = handled_files_path(app) else {
+        return HandledFiles::new();
+    };
+    match tokio::fs::read_to_string(&path).await {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+        Err(_) => HandledFiles::new(),
+    }
+}
+
+async fn save_handled_files(
+    app: &AppHandle,
+    handled: &HandledFiles,
+) -> Result<(), ConversionError> {
+    let path = handled_files_path(app)?;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| ConversionError::from_io(e, parent))?;
+    }
+    let content = serde_json::to_string(handled).map_err(|e| ConversionError::InvalidConfig {
+        message: format!("Failed to serialize handled watch files: {}", e),
+    })?;
+    tokio::fs::write(&path, content)
+        .await
+        .map_err(|e| ConversionError::from_io(e, &path))
+}
+
+// Tracks the files of one watch folder between polls.
+struct FolderTracker {
+    path: String,
+    recursive: bool,
+    // Last observed size of each unhandled file and when it last changed.
+    pending: HashMap<PathBuf, (u64, Instant)>,
+    // Sent to the job queue and not finished yet.
+    queued: HashSet<PathBuf>,
+    // Size at which a file failed; it is retried once that changes.
+    failed: HashMap<PathBuf, u64>,
+}
+
+impl FolderTracker {
+    fn new(settings: &WatchFolderSettings) -> Self {
+        Self {
+            path: settings.path.clone(),
+            recursive: settings.recursive,
+            pending: HashMap::new(),
+            queued: HashSet::new(),
+            failed: HashMap::new(),
+        }
+    }
+
+    fn matches(&self, settings: &WatchFolderSettings) -> bool {
+        self.path == settings.path && self.recursive == settings.recursive
+    }
+
+    // Returns the unhandled files whose size has held steady for `settle`,
+    // with that size. Files that are gone are dropped from `handled`.
+    fn settled(
+        &mut self,
+        files: Vec<FileInfo>,
+        settle: Duration,
+        handled: &mut HashSet<PathBuf>,
+    ) -> Vec<(PathBuf, u64)> {
+        let present: HashSet<PathBuf> = files.iter().map(|f| PathBuf::from(&f.path)).collect();
+        handled.retain(|p| present.contains(p));
+        self.pending.retain(|p, _| present.contains(p));
+        self.failed.retain(|p, _| present.contains(p));
+
+        let now = Instant::now();
+        let mut ready = Vec::new();
+        for file in files {
+            let path = PathBuf::from(file.path);
+            if handled.contains(&path)
+                || self.queued.contains(&path)
+                || self.failed.get(&path) == Some(&file.size)
+            {
+                continue;
+            }
+            match self.pending.get(&path) {
+                Some((size, since)) if *size == file.size => {
+                    if now.duration_since(*since) >= settle {
+                        self.pending.remove(&path);
+                        self.queued.insert(path.clone());
+                        ready.push((path, file.size));
+                    }
+                }
+                _ => {
+                    self.pending.insert(path, (file.size, now));
+                }
+            }
+        }
+        ready
+    }
+
+    fn finish(&mut self, outcome: &JobOutcome) {
+        self.queued.remove(&outcome.path);
+        if outcome.handled {
+            self.failed.remove(&outcome.path);
+        } else {
+            self.failed.insert(outcome.path.clone(), outcome.size);
+        }
+    }
+}
+
+async fn poll(
+    app: AppHandle,
+    jobs: mpsc::UnboundedSender<WatchJob>,
+    mut outcomes: mpsc::UnboundedReceiver<JobOutcome>,
+) {
+    let mut trackers: HashMap<String, FolderTracker> = HashMap::new();
+    let mut handled = load_handled_files(&app).await;
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        let Ok(folders) = list_watch_folders(&app).await else {
+            continue;
+        };
+        let mut changed = false;
+
+        while let Ok(outcome) = outcomes.try_recv() {
+            if let Some(tracker) = trackers.get_mut(&outcome.watch_id) {
+                tracker.finish(&outcome);
+            }
+            if outcome.handled {
+                changed |= handled
+                    .entry(outcome.watch_id)
+                    .or_default()
+                    .insert(outcome.path);
+            }
+        }
+
+        let known = handled.len();
+        handled.retain(|id, _| folders.iter().any(|f| &f.id == id));
+        changed |= handled.len() != known;
+
+        let folders: Vec<WatchFolder> =
+            folders.into_iter().filter(|f| f.settings.enabled).collect();
+
+        // Folders that were removed, disabled or repointed start over.
+        trackers.retain(|id, tracker| {
+            folders
+                .iter()
+                .any(|f| &f.id == id && tracker.matches(&f.settings))
+        });
+
+        for folder in folders {
+            // An unreachable folder, such as an offline share, would look
+            // empty and forget every handled file.
+            if !tokio::fs::try_exists(&folder.settings.path)
+                .await
+                .unwrap_or(false)
+            {
+                continue;
+            }
+            let files = list_media_files(&folder.settings).await;
+            let tracker = trackers
+                .entry(folder.id.clone())
+                .or_insert_with(|| FolderTracker::new(&folder.settings));
+            let handled_files = handled.entry(folder.id.clone()).or_default();
+            let known = handled_files.len();
+            let settle = Duration::from_secs(folder.settings.settle_secs);
+            let ready = tracker.settled(files, settle, handled_files);
+            changed |= handled_files.len() != known;
+
+            for (path, size) in ready {
+                let job = WatchJob {
+                    folder: folder.clone(),
+                    job_id: generate_job_id(&folder.id),
+                    path,
+                    size,
+                };
+                let _ = app.emit(
+                    "watch-event",
+                    watch_event(&job, WatchEventKind::Detected, None, None),
+                );
+                if jobs.send(job).is_err() {
+                    return;
+                }
+            }
+        }
+
+        if changed {
+            let _ = save_handled_files(&app, &handled).await;
+        }
+    }
+}
+
+async fn list_media_files(settings: &WatchFolderSettings) -> Vec<FileInfo> {
+    let roots = vec![PathBuf::from(&settings.path)];
+    let max_depth = (!settings.recursive).then_some(0);
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut files = Vec::new();
+        scanner::scan(
+            &roots,
+            max_depth,
+            &scanner::ScanFilter::default(),
+            &AtomicBool::new(false),
+            usize::MAX,
+            |chunk, _| files.extend(chunk),
+        );
+        files
+    })
+    .await
+    .unwrap_or_default()
+}
+
+async fn run_jobs(
+    app: AppHandle,
+    mut jobs: mpsc::UnboundedReceiver<WatchJob>,
+    outcomes: mpsc::UnboundedSender<JobOutcome>,
+) {
+    while let Some(job) = jobs.recv().await {
+        let settings = &job.folder.settings;
+        let mut request =
+            ConversionRequest::new(job.job_id.clone(), job.path.to_string_lossy().to_string());
+        request.preset_id = Some(settings.preset_id.clone());
+        request.output_directory = Some(settings.output_directory.clone());
+        request.conflict_mode = settings.conflict_mode;
+        request.source_action = settings.source_action.clone();
+
+        let result = commands::run_conversion(app.clone(), &app.state::<AppState>(), request).await;
+        let (kind, output_path, message) = match result {
+            Ok(result) if result.skipped => (WatchEventKind::Skipped, result.output_path, None),
+            Ok(result) if result.success => {
+                let message = result
+                    .source_action
+                    .and_then(|report| report.error_message)
+                    .map(|e| format!("Converted, but the source was left in place: {}", e));
+                (WatchEventKind::Converted, result.output_path, message)
+            }
+            Ok(result) => (WatchEventKind::Failed, None, result.error_message),
+            Err(e) => (WatchEventKind::Failed, None, Some(e.to_string())),
+        };
+        let _ = outcomes.send(JobOutcome {
+            watch_id: job.folder.id.clone(),
+            path: job.path.clone(),
+            size: job.size,
+            handled: kind != WatchEventKind::Failed,
+        });
+        let _ = app.emit("watch-event", watch_event(&job, kind, output_path, message));
+    }
+}
+
+fn generate_job_id(watch_id: &str) -> String {
+    use rand::Rng;
+
+    let suffix: u32 = rand::rng().random();
+    format!("{}-{:08x}", watch_id, suffix)
+}
+
+fn watch_event(
+    job: &WatchJob,
+    kind: WatchEventKind,
+    output_path: Option<PathBuf>,
+    message: Option<String>,
+) -> WatchEvent {
+    WatchEvent {
+        watch_id: job.folder.id.clone(),
+        job_id: job.job_id.clone(),
+        path: job.path.to_string_lossy().to_string(),
+        kind,
+        output_path,
+        message,
+    }
+}
+
+fn invalid_field(field: &str, message: String) -> ConversionError {
+    ConversionError::InvalidConfig {
+        message: format!("{}: {}", field, message),
+    }
+}
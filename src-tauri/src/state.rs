@@ -1,19 +1,36 @@
+use crate::error::ConversionError;
+use crate::types::{BatchSummary, ConversionResult};
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::process::Child;
 use tokio::sync::Mutex;
 
 pub struct AppState {
     pub running_processes: Arc<Mutex<HashMap<String, Child>>>,
-    pub output_paths: Arc<Mutex<HashMap<String, PathBuf>>>,
+    pub temp_paths: Arc<Mutex<HashMap<String, PathBuf>>>,
+    batch: Arc<Mutex<BatchTracker>>,
+    /// Cancellation flags of running directory scans.
+    scans: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
+}
+
+// Jobs that overlap in time count as one batch; it ends when none are active.
+#[derive(Default)]
+struct BatchTracker {
+    active: usize,
+    started: Option<Instant>,
+    summary: BatchSummary,
 }
 
 impl AppState {
     pub fn new() -> Self {
         Self {
             running_processes: Arc::new(Mutex::new(HashMap::new())),
-            output_paths: Arc::new(Mutex::new(HashMap::new())),
+            temp_paths: Arc::new(Mutex::new(HashMap::new())),
+            batch: Arc::new(Mutex::new(BatchTracker::default())),
+            scans: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -22,8 +39,8 @@ impl AppState {
         processes.insert(id, child);
     }
 
-    pub async fn register_output_path(&self, id: String, path: PathBuf) {
-        let mut paths = self.output_paths.lock().await;
+    pub async fn register_temp_path(&self, id: String, path: PathBuf) {
+        let mut paths = self.temp_paths.lock().await;
         paths.insert(id, path);
     }
 
@@ -32,26 +49,100 @@ impl AppState {
         processes.remove(id)
     }
 
-    pub async fn remove_output_path(&self, id: &str) -> Option<PathBuf> {
-        let mut paths = self.output_paths.lock().await;
+    pub async fn remove_temp_path(&self, id: &str) -> Option<PathBuf> {
+        let mut paths = self.temp_paths.lock().await;
         paths.remove(id)
     }
 
-    pub async fn kill_process(&self, id: &str) -> Result<(), String> {
+    pub async fn begin_job(&self) {
+        let mut batch = self.batch.lock().await;
+        if batch.active == 0 {
+            batch.started = Some(Instant::now());
+            batch.summary = BatchSummary::default();
+        }
+        batch.active += 1;
+    }
+
+    /// Records a finished job and returns the batch summary once the last
+    /// active job is done.
+    pub async fn finish_job(
+        &self,
+        result: &Result<ConversionResult, ConversionError>,
+    ) -> Option<BatchSummary> {
+        let mut batch = self.batch.lock().await;
+        batch.active = batch.active.saturating_sub(1);
+
+        let summary = &mut batch.summary;
+        summary.total += 1;
+        match result {
+            Ok(r) if r.skipped => summary.skipped += 1,
+            Ok(r) if r.success => {
+                summary.succeeded += 1;
+                if let Some(stats) = &r.stats {
+                    summary.input_bytes += stats.input_bytes;
+                    summary.output_bytes += stats.output_bytes;
+                }
+            }
+            Err(ConversionError::Cancelled { .. }) => summary.cancelled += 1,
+            _ => summary.failed += 1,
+        }
+
+        if batch.active > 0 {
+            return None;
+        }
+
+        let mut summary = std::mem::take(&mut batch.summary);
+        summary.compression_ratio = (summary.input_bytes > 0)
+            .then(|| summary.output_bytes as f64 / summary.input_bytes as f64);
+        summary.elapsed_ms = batch
+            .started
+            .take()
+            .map(|t| t.elapsed().as_millis() as u64)
+            .unwrap_or_default();
+        Some(summary)
+    }
+
+    pub async fn begin_scan(&self, id: &str) -> Arc<AtomicBool> {
+        let flag = Arc::new(AtomicBool::new(false));
+        let mut scans = self.scans.lock().await;
+        scans.insert(id.to_string(), flag.clone());
+        flag
+    }
+
+    pub async fn end_scan(&self, id: &str) {
+        let mut scans = self.scans.lock().await;
+        scans.remove(id);
+    }
+
+    /// Returns whether a scan with this id was running.
+    pub async fn cancel_scan(&self, id: &str) -> bool {
+        let scans = self.scans.lock().await;
+        match scans.get(id) {
+            Some(flag) => {
+                flag.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub async fn kill_process(&self, id: &str) -> Result<(), ConversionError> {
         let mut processes = self.running_processes.lock().await;
 
         if let Some(child) = processes.get_mut(id) {
             if let Err(e) = child.kill().await {
-                return Err(format!("Failed to kill process {}: {}", id, e));
+                return Err(ConversionError::IoError {
+                    message: format!("Failed to kill process {}: {}", id, e),
+                });
             }
             processes.remove(id);
         }
 
         drop(processes);
 
-        if let Some(output_path) = self.remove_output_path(id).await {
-            if output_path.exists() {
-                let _ = tokio::fs::remove_file(&output_path).await;
+        if let Some(temp_path) = self.remove_temp_path(id).await {
+            if temp_path.exists() {
+                let _ = tokio::fs::remove_file(&temp_path).await;
             }
         }
 
@@ -65,7 +156,7 @@ impl AppState {
         }
         processes.clear();
 
-        let mut paths = self.output_paths.lock().await;
+        let mut paths = self.temp_paths.lock().await;
         for (_id, path) in paths.iter() {
             if path.exists() {
                 let _ = tokio::fs::remove_file(path).await;
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -25,6 +26,7 @@ pub enum AudioFormat {
     Aac,
     M4a,
     Ogg,
+    Flac,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -54,6 +56,7 @@ impl OutputFormat {
                 AudioFormat::Aac => "aac",
                 AudioFormat::M4a => "m4a",
                 AudioFormat::Ogg => "ogg",
+                AudioFormat::Flac => "flac",
             },
         }
     }
@@ -110,9 +113,62 @@ pub enum BackgroundColor {
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum NamingBlock {
     Original,
-    Prefix { value: String },
-    Random { length: u8 },
+    Prefix {
+        value: String,
+    },
+    Random {
+        length: u8,
+    },
     Date,
+    Counter {
+        #[serde(default = "default_counter_start")]
+        start: u64,
+        #[serde(default = "default_counter_step")]
+        step: u64,
+        #[serde(default)]
+        padding: u8,
+    },
+    Resolution {
+        #[serde(default)]
+        fallback: String,
+    },
+    Duration {
+        #[serde(default)]
+        fallback: String,
+    },
+    Format,
+    Preset {
+        #[serde(default)]
+        fallback: String,
+    },
+    Extension,
+    ParentFolder {
+        #[serde(default)]
+        fallback: String,
+    },
+    Metadata {
+        field: MetadataField,
+        #[serde(default)]
+        fallback: String,
+    },
+}
+
+fn default_counter_start() -> u64 {
+    1
+}
+
+fn default_counter_step() -> u64 {
+    1
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MetadataField {
+    DateTaken,
+    Title,
+    Artist,
+    Album,
+    Camera,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -129,6 +185,23 @@ pub struct ResizeConfig {
 pub struct NamingConfig {
     pub blocks: Vec<NamingBlock>,
     pub sanitize_enabled: bool,
+    #[serde(default)]
+    pub sanitize_mode: SanitizeMode,
+    /// Keeps the original letter case instead of lowercasing when sanitizing.
+    #[serde(default)]
+    pub preserve_case: bool,
+    #[serde(default = "default_naming_separator")]
+    pub separator: String,
+    /// Replaces the blocks when set.
+    #[serde(default)]
+    pub template: Option<String>,
+    /// Applied in order to the original name before it is used.
+    #[serde(default)]
+    pub rules: Vec<RenameRule>,
+}
+
+fn default_naming_separator() -> String {
+    "_".to_string()
 }
 
 impl Default for NamingConfig {
@@ -136,45 +209,549 @@ impl Default for NamingConfig {
         Self {
             blocks: vec![NamingBlock::Original],
             sanitize_enabled: false,
+            sanitize_mode: SanitizeMode::default(),
+            preserve_case: false,
+            separator: default_naming_separator(),
+            template: None,
+            rules: Vec::new(),
         }
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RenameRule {
+    /// Literal find and replace of every occurrence.
+    Replace {
+        find: String,
+        #[serde(default)]
+        replace: String,
+    },
+    /// Regex replace; `$1` or `${name}` in `replace` insert capture groups.
+    Regex {
+        pattern: String,
+        #[serde(default)]
+        replace: String,
+    },
+    /// Trims whitespace, or the given characters when `chars` is set.
+    Trim {
+        #[serde(default)]
+        chars: String,
+    },
+    Truncate {
+        max_length: usize,
+    },
+    Case {
+        case: CaseTransform,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SanitizeMode {
+    /// Keeps only ASCII letters, digits, `_` and `-`.
+    #[default]
+    Ascii,
+    /// Transliterates accented Latin, Cyrillic and Greek letters to ASCII,
+    /// then applies the `Ascii` rules.
+    Transliterate,
+    /// Keeps Unicode and strips only characters that are illegal in file
+    /// names.
+    Unicode,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CaseTransform {
+    Lower,
+    Upper,
+    Title,
+    Kebab,
+    Snake,
+}
+
+// The size modes are settled against the finished output, not the source.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictMode {
+    #[default]
+    Skip,
+    Overwrite,
+    OverwriteIfOlder,
+    OverwriteIfLarger,
+    OverwriteIfSmaller,
+    KeepBoth,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ConversionRequest {
     pub id: String,
     pub input_path: String,
+    #[serde(default)]
+    pub preset_id: Option<String>,
+    #[serde(default)]
+    pub preset_overrides: Option<PresetOverrides>,
+    #[serde(default)]
     pub output_format: String,
+    #[serde(default)]
     pub quality_value: u16,
+    #[serde(default)]
     pub strip_metadata: bool,
+    #[serde(default)]
     pub is_muted: bool,
+    #[serde(default)]
     pub resize_config: Option<ResizeConfig>,
+    #[serde(default)]
     pub naming_config: Option<NamingConfig>,
+    #[serde(default)]
     pub output_directory: Option<String>,
-    pub conflict_mode: String,
+    #[serde(default)]
+    pub conflict_mode: ConflictMode,
+    #[serde(default)]
+    pub conflict_suffix: Option<String>,
     #[serde(default = "default_processing_enabled")]
     pub processing_enabled: bool,
+    #[serde(default)]
     pub max_bitrate: Option<u64>,
+    /// Lowers the video bitrate so the output fits in this many kilobytes.
+    #[serde(default)]
+    pub target_size_kb: Option<u64>,
+    #[serde(default)]
     pub video_preset: Option<String>,
+    #[serde(default)]
+    pub extra_input_args: Vec<String>,
+    #[serde(default)]
+    pub extra_output_args: Vec<String>,
+    #[serde(default)]
+    pub verify_output: bool,
+    #[serde(default)]
+    pub oversize_config: Option<OversizeConfig>,
+    #[serde(default)]
+    pub preserve_timestamps: bool,
+    /// When set, all other metadata is stripped and only these tags are kept.
+    #[serde(default)]
+    pub keep_metadata: Option<Vec<MetadataTag>>,
+    /// Tags written to the output, applied after `keep_metadata`.
+    #[serde(default)]
+    pub set_metadata: BTreeMap<String, String>,
+    #[serde(default)]
+    pub cover_art: Option<CoverArtSource>,
+    #[serde(default)]
+    pub chapters: ChapterMode,
+    /// Position of the file in its batch; drives counter naming blocks.
+    #[serde(default)]
+    pub batch_index: Option<u64>,
+    /// Recreates the input's folders below `base_directory` inside
+    /// `output_directory`.
+    #[serde(default)]
+    pub preserve_structure: bool,
+    #[serde(default)]
+    pub base_directory: Option<String>,
+    #[serde(default)]
+    pub source_action: SourceAction,
 }
 
 fn default_processing_enabled() -> bool {
     true
 }
 
+impl ConversionRequest {
+    /// A request with the same defaults as one deserialized with only an id
+    /// and input path.
+    pub fn new(id: String, input_path: String) -> Self {
+        Self {
+            id,
+            input_path,
+            preset_id: None,
+            preset_overrides: None,
+            output_format: String::new(),
+            quality_value: 0,
+            strip_metadata: false,
+            is_muted: false,
+            resize_config: None,
+            naming_config: None,
+            output_directory: None,
+            conflict_mode: ConflictMode::default(),
+            conflict_suffix: None,
+            processing_enabled: default_processing_enabled(),
+            max_bitrate: None,
+            target_size_kb: None,
+            video_preset: None,
+            extra_input_args: Vec::new(),
+            extra_output_args: Vec::new(),
+            verify_output: false,
+            oversize_config: None,
+            preserve_timestamps: false,
+            keep_metadata: None,
+            set_metadata: BTreeMap::new(),
+            cover_art: None,
+            chapters: ChapterMode::default(),
+            batch_index: None,
+            preserve_structure: false,
+            base_directory: None,
+            source_action: SourceAction::default(),
+        }
+    }
+}
+
+/// What happens to the source file once its output has been written and has
+/// passed verification.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum SourceAction {
+    #[default]
+    #[serde(alias = "keep")]
+    Leave,
+    Trash,
+    /// Relative directories are resolved against the source's folder.
+    Move {
+        directory: String,
+    },
+    Delete,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceActionReport {
+    pub action: SourceAction,
+    pub success: bool,
+    /// Where the source ended up when it was moved.
+    pub moved_to: Option<PathBuf>,
+    pub error_message: Option<String>,
+}
+
+/// Tags that survive when a metadata whitelist is set on a request.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MetadataTag {
+    CreationTime,
+    Gps,
+    Title,
+    Artist,
+    Copyright,
+}
+
+impl MetadataTag {
+    // Container keys ffmpeg reports for each tag, compared case-insensitively.
+    pub fn ffmpeg_keys(&self) -> &'static [&'static str] {
+        match self {
+            MetadataTag::CreationTime => {
+                &["creation_time", "date", "com.apple.quicktime.creationdate"]
+            }
+            MetadataTag::Gps => &[
+                "location",
+                "location-eng",
+                "com.apple.quicktime.location.iso6709",
+            ],
+            MetadataTag::Title => &["title"],
+            MetadataTag::Artist => &["artist", "album_artist"],
+            MetadataTag::Copyright => &["copyright"],
+        }
+    }
+}
+
+/// How chapters are carried into the output, independently of tags.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum ChapterMode {
+    /// Keep source chapters unless `strip_metadata` is set.
+    #[default]
+    Auto,
+    Keep,
+    Strip,
+    /// Replace the chapters with those from a text or JSON chapter file.
+    Import {
+        path: String,
+    },
+}
+
+/// Where embedded cover art for audio outputs comes from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum CoverArtSource {
+    Image {
+        path: String,
+    },
+    VideoFrame {
+        #[serde(default)]
+        time_secs: Option<f64>,
+    },
+}
+
+/// What to do when the output ends up larger than the source.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OversizePolicy {
+    /// Discard the output and copy the source to the output directory instead.
+    CopyOriginal,
+    /// Discard the output and remux the source, in its own format, to the
+    /// output directory instead.
+    KeepOriginalFormat,
+    /// Keep the output but mark the result as oversized.
+    Flag,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct OversizeConfig {
+    pub policy: OversizePolicy,
+    /// How much larger than the source the output may get before the policy
+    /// applies, in percent.
+    #[serde(default)]
+    pub threshold_percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PresetSettings {
+    pub output_format: String,
+    pub quality_value: u16,
+    #[serde(default)]
+    pub strip_metadata: bool,
+    #[serde(default)]
+    pub is_muted: bool,
+    #[serde(default)]
+    pub resize_config: Option<ResizeConfig>,
+    #[serde(default)]
+    pub naming_config: Option<NamingConfig>,
+    #[serde(default = "default_processing_enabled")]
+    pub processing_enabled: bool,
+    #[serde(default)]
+    pub max_bitrate: Option<u64>,
+    #[serde(default)]
+    pub target_size_kb: Option<u64>,
+    #[serde(default)]
+    pub video_preset: Option<String>,
+    #[serde(default)]
+    pub extra_input_args: Vec<String>,
+    #[serde(default)]
+    pub extra_output_args: Vec<String>,
+}
+
+impl PresetSettings {
+    pub fn apply_to(&self, request: &mut ConversionRequest) {
+        request.output_format = self.output_format.clone();
+        request.quality_value = self.quality_value;
+        request.strip_metadata = self.strip_metadata;
+        request.is_muted = self.is_muted;
+        request.resize_config = self.resize_config.clone();
+        request.naming_config = self.naming_config.clone();
+        request.processing_enabled = self.processing_enabled;
+        request.max_bitrate = self.max_bitrate;
+        request.target_size_kb = self.target_size_kb;
+        request.video_preset = self.video_preset.clone();
+        request.extra_input_args = self.extra_input_args.clone();
+        request.extra_output_args = self.extra_output_args.clone();
+    }
+}
+
+/// Optional fields take `null` to clear the preset's value, while a missing
+/// field keeps it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PresetOverrides {
+    pub output_format: Option<String>,
+    pub quality_value: Option<u16>,
+    pub strip_metadata: Option<bool>,
+    pub is_muted: Option<bool>,
+    #[serde(
+        default,
+        deserialize_with = "clearable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub resize_config: Option<Option<ResizeConfig>>,
+    #[serde(
+        default,
+        deserialize_with = "clearable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub naming_config: Option<Option<NamingConfig>>,
+    pub processing_enabled: Option<bool>,
+    #[serde(
+        default,
+        deserialize_with = "clearable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_bitrate: Option<Option<u64>>,
+    #[serde(
+        default,
+        deserialize_with = "clearable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub target_size_kb: Option<Option<u64>>,
+    #[serde(
+        default,
+        deserialize_with = "clearable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub video_preset: Option<Option<String>>,
+    pub extra_input_args: Option<Vec<String>>,
+    pub extra_output_args: Option<Vec<String>>,
+}
+
+// Only called for fields that are present, so `null` becomes `Some(None)`.
+fn clearable<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: serde::Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+impl PresetOverrides {
+    pub fn apply_to(&self, request: &mut ConversionRequest) {
+        if let Some(ref v) = self.output_format {
+            request.output_format = v.clone();
+        }
+        if let Some(v) = self.quality_value {
+            request.quality_value = v;
+        }
+        if let Some(v) = self.strip_metadata {
+            request.strip_metadata = v;
+        }
+        if let Some(v) = self.is_muted {
+            request.is_muted = v;
+        }
+        if let Some(ref v) = self.resize_config {
+            request.resize_config = v.clone();
+        }
+        if let Some(ref v) = self.naming_config {
+            request.naming_config = v.clone();
+        }
+        if let Some(v) = self.processing_enabled {
+            request.processing_enabled = v;
+        }
+        if let Some(v) = self.max_bitrate {
+            request.max_bitrate = v;
+        }
+        if let Some(v) = self.target_size_kb {
+            request.target_size_kb = v;
+        }
+        if let Some(ref v) = self.video_preset {
+            request.video_preset = v.clone();
+        }
+        if let Some(ref v) = self.extra_input_args {
+            request.extra_input_args = v.clone();
+        }
+        if let Some(ref v) = self.extra_output_args {
+            request.extra_output_args = v.clone();
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ConversionPreset {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub built_in: bool,
+    pub settings: PresetSettings,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PresetFile {
+    pub schema_version: u32,
+    pub presets: Vec<ExportedPreset>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ExportedPreset {
+    pub name: String,
+    pub settings: PresetSettings,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetConflict {
+    pub name: String,
+    pub existing_id: String,
+    pub built_in: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetImportReport {
+    pub imported: Vec<ConversionPreset>,
+    pub replaced: Vec<ConversionPreset>,
+    pub conflicts: Vec<PresetConflict>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QualitySetting {
+    Crf(u8),
+    ImageQscale(u8),
+    WebpQuality(u8),
+    Lossless,
+    AudioVbr(u8),
+    AudioBitrateKbps(u16),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VideoPreset {
+    Ultrafast,
+    Superfast,
+    Veryfast,
+    Faster,
+    Fast,
+    Medium,
+    Slow,
+    Slower,
+    Veryslow,
+}
+
+impl VideoPreset {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "ultrafast" => Some(VideoPreset::Ultrafast),
+            "superfast" => Some(VideoPreset::Superfast),
+            "veryfast" => Some(VideoPreset::Veryfast),
+            "faster" => Some(VideoPreset::Faster),
+            "fast" => Some(VideoPreset::Fast),
+            "medium" => Some(VideoPreset::Medium),
+            "slow" => Some(VideoPreset::Slow),
+            "slower" => Some(VideoPreset::Slower),
+            "veryslow" => Some(VideoPreset::Veryslow),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VideoPreset::Ultrafast => "ultrafast",
+            VideoPreset::Superfast => "superfast",
+            VideoPreset::Veryfast => "veryfast",
+            VideoPreset::Faster => "faster",
+            VideoPreset::Fast => "fast",
+            VideoPreset::Medium => "medium",
+            VideoPreset::Slow => "slow",
+            VideoPreset::Slower => "slower",
+            VideoPreset::Veryslow => "veryslow",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ConversionConfig {
     pub input_path: PathBuf,
     pub output_path: PathBuf,
     pub output_format: OutputFormat,
-    pub quality_value: u16,
+    pub quality: QualitySetting,
     pub resize_config: Option<ResizeConfig>,
     pub is_muted: bool,
     pub strip_metadata: bool,
-    pub conflict_mode: String,
+    pub metadata_tags: Vec<(String, String)>,
+    pub cover_art_path: Option<PathBuf>,
+    pub keep_chapters: bool,
+    pub chapters_file: Option<PathBuf>,
+    pub overwrite: bool,
     pub max_bitrate: Option<u64>,
-    pub video_preset: String,
+    pub video_preset: VideoPreset,
+    pub extra_input_args: Vec<String>,
+    pub extra_output_args: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -202,8 +779,109 @@ pub struct ProgressEvent {
 pub struct ConversionResult {
     pub id: String,
     pub success: bool,
+    pub skipped: bool,
     pub output_path: Option<PathBuf>,
     pub error_message: Option<String>,
+    pub verification: Option<VerificationReport>,
+    pub stats: Option<ConversionStats>,
+    pub oversize: Option<OversizeReport>,
+    /// Set when the request asked for something other than leaving the
+    /// source in place.
+    pub source_action: Option<SourceActionReport>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OversizeReport {
+    pub policy: OversizePolicy,
+    pub threshold_percent: f64,
+    pub input_bytes: u64,
+    pub output_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversionStats {
+    pub input_bytes: u64,
+    pub output_bytes: u64,
+    /// Output size divided by input size; below 1.0 means the file shrank.
+    pub compression_ratio: Option<f64>,
+    pub elapsed_ms: u64,
+    /// Seconds of media produced per second of wall time.
+    pub speed: Option<f64>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub duration_secs: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchSummary {
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub cancelled: usize,
+    pub input_bytes: u64,
+    pub output_bytes: u64,
+    pub compression_ratio: Option<f64>,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationCheckKind {
+    Streams,
+    Duration,
+    Decode,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationCheck {
+    pub kind: VerificationCheckKind,
+    pub passed: bool,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationReport {
+    pub passed: bool,
+    pub checks: Vec<VerificationCheck>,
+}
+
+impl VerificationReport {
+    pub fn failures(&self) -> Vec<String> {
+        self.checks
+            .iter()
+            .filter(|c| !c.passed)
+            .map(|c| c.message.clone())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(
+    tag = "type",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum ConflictResolution {
+    None,
+    Overwrite,
+    Renamed { original_path: PathBuf },
+    Skipped { existing_path: PathBuf },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandPreview {
+    pub output_path: PathBuf,
+    pub conflict: ConflictResolution,
+    pub program: Option<String>,
+    pub args: Vec<String>,
+    pub command_line: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -232,6 +910,165 @@ pub struct FileInfo {
     pub media_type: MediaType,
 }
 
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileMetadata {
+    pub path: String,
+    pub format_tags: BTreeMap<String, String>,
+    pub streams: Vec<StreamMetadata>,
+    pub chapters: Vec<Chapter>,
+    /// Embedded cover art, extracted to a temp file like thumbnails.
+    pub cover_art_path: Option<PathBuf>,
+    pub exif: Option<ExifData>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamMetadata {
+    pub index: usize,
+    pub kind: String,
+    pub codec: String,
+    pub attached_pic: bool,
+    pub tags: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Chapter {
+    pub start_secs: f64,
+    pub end_secs: f64,
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExifData {
+    pub camera_make: Option<String>,
+    pub camera_model: Option<String>,
+    pub lens_model: Option<String>,
+    pub exposure_time: Option<String>,
+    pub f_number: Option<f64>,
+    pub iso: Option<u32>,
+    pub focal_length_mm: Option<f64>,
+    pub date_taken: Option<String>,
+    pub orientation: Option<u16>,
+    pub gps: Option<GpsCoordinates>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GpsCoordinates {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct WatchFolder {
+    pub id: String,
+    pub settings: WatchFolderSettings,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct WatchFolderSettings {
+    pub path: String,
+    pub preset_id: String,
+    pub output_directory: String,
+    #[serde(default)]
+    pub recursive: bool,
+    #[serde(default = "default_watch_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub conflict_mode: ConflictMode,
+    /// How long a new file's size must stay unchanged before it is converted.
+    #[serde(default = "default_settle_secs")]
+    pub settle_secs: u64,
+    /// Applied to each watched file once it converted successfully.
+    #[serde(default)]
+    pub source_action: SourceAction,
+}
+
+fn default_watch_enabled() -> bool {
+    true
+}
+
+fn default_settle_secs() -> u64 {
+    5
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchEventKind {
+    Detected,
+    Converted,
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchEvent {
+    pub watch_id: String,
+    pub job_id: String,
+    pub path: String,
+    pub kind: WatchEventKind,
+    pub output_path: Option<PathBuf>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ScanRequest {
+    pub id: String,
+    /// Directories to walk; plain files are checked against the filters too.
+    pub paths: Vec<String>,
+    /// How many directory levels below each path to descend; unlimited when
+    /// unset.
+    #[serde(default)]
+    pub max_depth: Option<usize>,
+    /// Empty means every media type.
+    #[serde(default)]
+    pub media_types: Vec<MediaType>,
+    /// Empty means every supported extension.
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    #[serde(default)]
+    pub min_size: Option<u64>,
+    #[serde(default)]
+    pub max_size: Option<u64>,
+    /// Unix time in milliseconds.
+    #[serde(default)]
+    pub modified_after: Option<u64>,
+    /// Unix time in milliseconds.
+    #[serde(default)]
+    pub modified_before: Option<u64>,
+    #[serde(default)]
+    pub include_hidden: bool,
+    /// Files per `scan-progress` event.
+    #[serde(default)]
+    pub chunk_size: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanChunk {
+    pub id: String,
+    pub files: Vec<FileInfo>,
+    /// Files looked at so far, matching or not.
+    pub scanned_files: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanSummary {
+    pub id: String,
+    pub found: usize,
+    pub scanned_files: usize,
+    pub cancelled: bool,
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FileInfoResult {
@@ -253,6 +1090,7 @@ pub fn parse_output_format(s: &str) -> Option<OutputFormat> {
         "aac" => Some(OutputFormat::Audio(AudioFormat::Aac)),
         "m4a" => Some(OutputFormat::Audio(AudioFormat::M4a)),
         "ogg" => Some(OutputFormat::Audio(AudioFormat::Ogg)),
+        "flac" => Some(OutputFormat::Audio(AudioFormat::Flac)),
         _ => None,
     }
 }
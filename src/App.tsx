@@ -19,7 +19,19 @@ import { useAppSettingsStore } from "@/store/appSettingsStore";
 import { useSettingsStore } from "@/store/settingsStore";
 
 import { getMediaType, ALL_EXTENSIONS } from "@/types";
-import { getFileName, getExtension, cn } from "@/lib/utils";
+import type {
+  ConversionError,
+  ScanChunk,
+  ScanSummary,
+  WatchEvent,
+} from "@/types";
+import {
+  getFileName,
+  getExtension,
+  cn,
+  generateId,
+  formatConversionError,
+} from "@/lib/utils";
 import { hasEffectiveOverride } from "@/lib/overrideUtils";
 import {
   TOOLBAR_HEIGHT,
@@ -27,6 +39,7 @@ import {
   PROCESS_DEBUG_DELAY,
   TAURI_COMMANDS,
   TAURI_EVENTS,
+  FOLDER_SCAN_MAX_DEPTH,
 } from "@/lib/constants";
 
 interface TauriDropPayload {
@@ -114,17 +127,74 @@ function App() {
     itemsRef.current = items;
   }, [items]);
 
+  const scanFolders = useCallback(
+    async (folders: string[]) => {
+      const scanId = generateId("scan");
+      let addedCount = 0;
+
+      const unlisten = await listen<ScanChunk>(
+        TAURI_EVENTS.SCAN_PROGRESS,
+        (event) => {
+          if (event.payload.id !== scanId) return;
+
+          const { newItems } = addFiles(event.payload.files);
+          addedCount += newItems.length;
+          newItems
+            .filter((item) => item.mediaType === "audio")
+            .forEach((item) => setThumbnailError(item.id));
+        },
+      );
+
+      try {
+        const summary = await invoke<ScanSummary>(
+          TAURI_COMMANDS.SCAN_DIRECTORY,
+          {
+            request: {
+              id: scanId,
+              paths: folders,
+              max_depth: FOLDER_SCAN_MAX_DEPTH,
+            },
+          },
+        );
+        addToast(
+          summary.cancelled
+            ? `Folder scan cancelled, ${addedCount} files added`
+            : `${addedCount} files added from folders`,
+          summary.cancelled ? "warning" : "success",
+        );
+      } catch (error) {
+        const conversionError = error as ConversionError;
+        addToast(
+          conversionError?.type
+            ? formatConversionError(conversionError)
+            : String(error),
+          "error",
+        );
+      } finally {
+        unlisten();
+      }
+    },
+    [addFiles, addToast, setThumbnailError],
+  );
+
   const handleFilePaths = useCallback(
     async (paths: string[]) => {
       if (paths.length === 0) return;
 
       const existingPaths = new Set(itemsRef.current.map((i) => i.inputPath));
       const newPaths: string[] = [];
+      // Dropped paths without an extension are treated as folders.
+      const folderPaths = paths.filter((path) => !getExtension(path));
       let existingCount = 0;
       let unsupportedCount = 0;
 
+      if (folderPaths.length > 0) {
+        scanFolders(folderPaths);
+      }
+
       for (const path of paths) {
         const ext = getExtension(path);
+        if (!ext) continue;
         const mediaType = getMediaType(ext);
         if (!mediaType) {
           unsupportedCount++;
@@ -234,6 +304,7 @@ function App() {
       startUpload,
       updateProcessedCount,
       finishUpload,
+      scanFolders,
     ],
   );
 
@@ -285,6 +356,24 @@ function App() {
       }),
     );
 
+    unlistenPromises.push(
+      listen<WatchEvent>(TAURI_EVENTS.WATCH_EVENT, (event) => {
+        const { kind, path, message } = event.payload;
+        const name = getFileName(path);
+        if (kind === "converted") {
+          addToast(
+            message ? `${name}: ${message}` : `Watch folder converted ${name}`,
+            message ? "warning" : "success",
+          );
+        } else if (kind === "failed") {
+          addToast(
+            `Watch folder failed on ${name}: ${message ?? "Unknown error"}`,
+            "error",
+          );
+        }
+      }),
+    );
+
     return () => {
       unlistenPromises.forEach((p) => p.then((unlisten) => unlisten()));
     };
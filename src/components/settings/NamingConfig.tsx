@@ -5,6 +5,14 @@ import {
   Type,
   Shuffle,
   Calendar,
+  Hash,
+  Maximize2,
+  Clock,
+  FileType,
+  SlidersHorizontal,
+  FileCode,
+  Folder,
+  Tags,
   Plus,
   X,
   GripVertical,
@@ -15,8 +23,15 @@ import * as DropdownMenu from "@radix-ui/react-dropdown-menu";
 import { AnimatePresence } from "framer-motion";
 import { cn, generateId } from "@/lib/utils";
 import { ToggleSwitch } from "@/components/ui/ToggleSwitch";
-import type { NamingBlock, NamingBlockType, NamingConfig } from "@/types";
-import { DEFAULT_RANDOM_LENGTH } from "@/types";
+import { RenameRules } from "./RenameRules";
+import type {
+  MetadataField,
+  NamingBlock,
+  NamingBlockType,
+  NamingConfig,
+  SanitizeMode,
+} from "@/types";
+import { DEFAULT_COUNTER_PADDING, DEFAULT_RANDOM_LENGTH } from "@/types";
 
 interface BlockDefinition {
   type: NamingBlockType;
@@ -53,8 +68,86 @@ const BLOCK_DEFINITIONS: BlockDefinition[] = [
     icon: Calendar,
     description: "Date stamp",
   },
+  {
+    type: "counter",
+    label: "Counter",
+    icon: Hash,
+    description: "Sequence number across the batch",
+    defaultParams: { start: 1, step: 1, padding: DEFAULT_COUNTER_PADDING },
+  },
+  {
+    type: "resolution",
+    label: "Resolution",
+    icon: Maximize2,
+    description: "Source width x height",
+  },
+  {
+    type: "duration",
+    label: "Duration",
+    icon: Clock,
+    description: "Source length, e.g. 3m07s",
+  },
+  {
+    type: "format",
+    label: "Output Format",
+    icon: FileType,
+    description: "Output file extension",
+  },
+  {
+    type: "preset",
+    label: "Preset",
+    icon: SlidersHorizontal,
+    description: "Name of the preset used",
+  },
+  {
+    type: "extension",
+    label: "Original Extension",
+    icon: FileCode,
+    description: "Source file extension",
+  },
+  {
+    type: "parent_folder",
+    label: "Parent Folder",
+    icon: Folder,
+    description: "Name of the source folder",
+  },
+  {
+    type: "metadata",
+    label: "Metadata",
+    icon: Tags,
+    description: "Date taken, title, artist, album or camera",
+    defaultParams: { field: "date_taken" },
+  },
+];
+
+const FALLBACK_BLOCKS: NamingBlockType[] = [
+  "resolution",
+  "duration",
+  "preset",
+  "parent_folder",
+  "metadata",
+];
+
+const METADATA_FIELDS: { value: MetadataField; label: string }[] = [
+  { value: "date_taken", label: "Date taken" },
+  { value: "title", label: "Title" },
+  { value: "artist", label: "Artist" },
+  { value: "album", label: "Album" },
+  { value: "camera", label: "Camera" },
 ];
 
+const SANITIZE_MODES: { value: SanitizeMode; label: string }[] = [
+  { value: "ascii", label: "ASCII only (drop other characters)" },
+  { value: "transliterate", label: "Transliterate to ASCII (ş → s)" },
+  { value: "unicode", label: "Keep Unicode, strip illegal characters" },
+];
+
+const COUNTER_PARAMS = [
+  { key: "start", label: "Start", min: 0 },
+  { key: "step", label: "Step", min: 1 },
+  { key: "padding", label: "Pad", min: 0 },
+] as const;
+
 interface NamingConfigProps {
   config: NamingConfig;
   onChange: (config: NamingConfig) => void;
@@ -157,6 +250,62 @@ const BlockItem = memo(function BlockItem({
               </span>
             </div>
           )}
+          {block.type === "counter" && (
+            <div className="flex items-center gap-2 mt-1">
+              {COUNTER_PARAMS.map(({ key, label, min }) => (
+                <label
+                  key={key}
+                  className="flex items-center gap-1 text-[10px] text-ash"
+                >
+                  {label}
+                  <input
+                    type="number"
+                    min={min}
+                    value={block.params?.[key] ?? min}
+                    onChange={(e) =>
+                      onUpdate(block.id, {
+                        ...block.params,
+                        [key]: Math.max(min, parseInt(e.target.value) || 0),
+                      })
+                    }
+                    onPointerDown={(e) => e.stopPropagation()}
+                    className="w-12 px-1.5 py-0.5 text-[10px] font-mono bg-slate/50 border border-border-subtle rounded text-snow focus:border-neon-cyan/50 focus:outline-none select-text"
+                  />
+                </label>
+              ))}
+            </div>
+          )}
+          {block.type === "metadata" && (
+            <select
+              value={block.params?.field || "date_taken"}
+              onChange={(e) =>
+                onUpdate(block.id, {
+                  ...block.params,
+                  field: e.target.value as MetadataField,
+                })
+              }
+              onPointerDown={(e) => e.stopPropagation()}
+              className="mt-1 w-full px-1.5 py-0.5 text-[10px] bg-slate/50 border border-border-subtle rounded text-snow focus:border-neon-cyan/50 focus:outline-none"
+            >
+              {METADATA_FIELDS.map((f) => (
+                <option key={f.value} value={f.value}>
+                  {f.label}
+                </option>
+              ))}
+            </select>
+          )}
+          {FALLBACK_BLOCKS.includes(block.type) && (
+            <input
+              type="text"
+              value={block.params?.fallback || ""}
+              onChange={(e) =>
+                onUpdate(block.id, { ...block.params, fallback: e.target.value })
+              }
+              onPointerDown={(e) => e.stopPropagation()}
+              placeholder="fallback when missing..."
+              className="mt-1 w-full px-1.5 py-0.5 text-[10px] font-mono bg-slate/50 border border-border-subtle rounded text-snow focus:border-neon-cyan/50 focus:outline-none placeholder:text-ash/50 select-text"
+            />
+          )}
         </div>
         {!isSingle && (
           <button
@@ -225,10 +374,40 @@ export function NamingConfig({ config, onChange }: NamingConfigProps) {
     onChange({ ...config, sanitizeEnabled: enabled });
   };
 
+  const usesTemplate = Boolean(config.template?.trim());
+
   return (
     <div className="space-y-3">
       <label className="text-xs font-medium text-smoke">Naming Strategy</label>
-      <div className="space-y-2 mb-4 mt-2">
+      <div className="space-y-1">
+        <input
+          type="text"
+          value={config.template || ""}
+          onChange={(e) => onChange({ ...config, template: e.target.value })}
+          placeholder="{date:%Y-%m-%d}-{original}-{w}x{h}-{counter:03}"
+          className="w-full px-2 py-1.5 text-xs font-mono bg-slate/50 border border-border-subtle rounded-lg text-snow focus:border-neon-cyan/50 focus:outline-none placeholder:text-ash/50"
+        />
+        <span className="text-[10px] text-ash block">
+          Template replaces the blocks below. Add |lower, |upper, |title,
+          |kebab or |snake to a placeholder to change its case.
+        </span>
+      </div>
+      <div
+        className={cn(
+          "space-y-2 mb-4 mt-2",
+          usesTemplate && "opacity-50 pointer-events-none",
+        )}
+      >
+        <div className="flex items-center justify-between">
+          <span className="text-[10px] text-ash">Separator</span>
+          <input
+            type="text"
+            value={config.separator ?? "_"}
+            onChange={(e) => onChange({ ...config, separator: e.target.value })}
+            maxLength={4}
+            className="w-12 px-1.5 py-0.5 text-[10px] font-mono text-center bg-slate/50 border border-border-subtle rounded text-snow focus:border-neon-cyan/50 focus:outline-none"
+          />
+        </div>
         <Reorder.Group
           axis="y"
           values={config.blocks}
@@ -322,6 +501,10 @@ export function NamingConfig({ config, onChange }: NamingConfigProps) {
           </AnimatePresence>
         </DropdownMenu.Root>
       </div>
+      <RenameRules
+        rules={config.rules ?? []}
+        onChange={(rules) => onChange({ ...config, rules })}
+      />
       <div className="flex items-center justify-between pt-4 border-t border-border-subtle">
         <div className="flex items-center gap-2">
           <Sparkles className="w-4 h-4 text-electric-violet" />
@@ -339,6 +522,34 @@ export function NamingConfig({ config, onChange }: NamingConfigProps) {
           onChange={handleSanitizeToggle}
         />
       </div>
+      {config.sanitizeEnabled && (
+        <div className="space-y-2 pl-6">
+          <select
+            value={config.sanitizeMode ?? "ascii"}
+            onChange={(e) =>
+              onChange({
+                ...config,
+                sanitizeMode: e.target.value as SanitizeMode,
+              })
+            }
+            className="w-full px-1.5 py-1 text-[10px] bg-slate/50 border border-border-subtle rounded text-snow focus:border-neon-cyan/50 focus:outline-none"
+          >
+            {SANITIZE_MODES.map((m) => (
+              <option key={m.value} value={m.value}>
+                {m.label}
+              </option>
+            ))}
+          </select>
+          <div className="flex items-center justify-between">
+            <span className="text-[10px] text-ash">Preserve letter case</span>
+            <ToggleSwitch
+              checked={config.preserveCase ?? false}
+              onChange={(preserveCase) => onChange({ ...config, preserveCase })}
+              size="sm"
+            />
+          </div>
+        </div>
+      )}
     </div>
   );
 }
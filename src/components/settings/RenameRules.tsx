@@ -0,0 +1,150 @@
+import { Plus, X } from "lucide-react";
+import { generateId } from "@/lib/utils";
+import type { CaseTransform, RenameRule, RenameRuleType } from "@/types";
+import { DEFAULT_TRUNCATE_LENGTH } from "@/types";
+
+const RULE_TYPES: { type: RenameRuleType; label: string }[] = [
+  { type: "replace", label: "Replace" },
+  { type: "regex", label: "Regex" },
+  { type: "trim", label: "Trim" },
+  { type: "truncate", label: "Truncate" },
+  { type: "case", label: "Case" },
+];
+
+const CASE_OPTIONS: CaseTransform[] = ["lower", "upper", "title", "kebab", "snake"];
+
+const inputClass =
+  "flex-1 min-w-0 px-1.5 py-0.5 text-[10px] font-mono bg-slate/50 border border-border-subtle rounded text-snow focus:border-neon-cyan/50 focus:outline-none placeholder:text-ash/50";
+
+interface RenameRulesProps {
+  rules: RenameRule[];
+  onChange: (rules: RenameRule[]) => void;
+}
+
+export function RenameRules({ rules, onChange }: RenameRulesProps) {
+  const updateRule = (id: string, patch: Partial<RenameRule>) => {
+    onChange(rules.map((r) => (r.id === id ? { ...r, ...patch } : r)));
+  };
+
+  const addRule = () => {
+    onChange([...rules, { id: generateId("rule"), type: "replace" }]);
+  };
+
+  const removeRule = (id: string) => {
+    onChange(rules.filter((r) => r.id !== id));
+  };
+
+  const renderFields = (rule: RenameRule) => {
+    switch (rule.type) {
+      case "replace":
+      case "regex":
+        return (
+          <>
+            <input
+              type="text"
+              value={(rule.type === "replace" ? rule.find : rule.pattern) || ""}
+              onChange={(e) =>
+                updateRule(
+                  rule.id,
+                  rule.type === "replace"
+                    ? { find: e.target.value }
+                    : { pattern: e.target.value },
+                )
+              }
+              placeholder={rule.type === "replace" ? "find" : "^IMG_(\\d+)"}
+              className={inputClass}
+            />
+            <input
+              type="text"
+              value={rule.replace || ""}
+              onChange={(e) => updateRule(rule.id, { replace: e.target.value })}
+              placeholder={rule.type === "replace" ? "replace" : "photo_$1"}
+              className={inputClass}
+            />
+          </>
+        );
+      case "trim":
+        return (
+          <input
+            type="text"
+            value={rule.chars || ""}
+            onChange={(e) => updateRule(rule.id, { chars: e.target.value })}
+            placeholder="characters (default: whitespace)"
+            className={inputClass}
+          />
+        );
+      case "truncate":
+        return (
+          <input
+            type="number"
+            min={1}
+            value={rule.maxLength ?? DEFAULT_TRUNCATE_LENGTH}
+            onChange={(e) =>
+              updateRule(rule.id, {
+                maxLength: Math.max(1, parseInt(e.target.value) || 1),
+              })
+            }
+            className={inputClass}
+          />
+        );
+      case "case":
+        return (
+          <select
+            value={rule.case || "lower"}
+            onChange={(e) =>
+              updateRule(rule.id, { case: e.target.value as CaseTransform })
+            }
+            className={inputClass}
+          >
+            {CASE_OPTIONS.map((c) => (
+              <option key={c} value={c}>
+                {c}
+              </option>
+            ))}
+          </select>
+        );
+    }
+  };
+
+  return (
+    <div className="space-y-1.5">
+      <span className="text-[10px] text-ash block">
+        Rename rules run in order on the original filename.
+      </span>
+      {rules.map((rule) => (
+        <div key={rule.id} className="flex items-center gap-1.5 group">
+          <select
+            value={rule.type}
+            onChange={(e) =>
+              updateRule(rule.id, { type: e.target.value as RenameRuleType })
+            }
+            className="w-20 px-1 py-0.5 text-[10px] bg-slate/50 border border-border-subtle rounded text-snow focus:border-neon-cyan/50 focus:outline-none"
+          >
+            {RULE_TYPES.map((t) => (
+              <option key={t.type} value={t.type}>
+                {t.label}
+              </option>
+            ))}
+          </select>
+          {renderFields(rule)}
+          <button
+            onClick={() => removeRule(rule.id)}
+            className="p-1 text-ash hover:text-plasma-pink transition-all hover:bg-plasma-pink/10 rounded-full"
+            title="Remove"
+          >
+            <X className="w-3 h-3" />
+          </button>
+        </div>
+      ))}
+      <button
+        onClick={addRule}
+        className="w-full flex items-center justify-center gap-1.5 px-2 py-1 rounded-lg border border-dashed border-border-subtle text-[10px] text-ash hover:border-neon-cyan/50 hover:text-neon-cyan transition-colors"
+      >
+        <Plus className="w-3 h-3" />
+        Add Rule
+      </button>
+    </div>
+  );
+}
+
+export default RenameRules;
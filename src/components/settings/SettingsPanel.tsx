@@ -14,8 +14,19 @@ import { QualityConfig } from "./QualityConfig";
 import { PresetSelector } from "./PresetSelector";
 import { ResizeConfig } from "./ResizeConfig";
 import { NamingConfig } from "./NamingConfig";
-import type { MediaType, ConversionSettings } from "@/types";
-import { getQualityConfigForFormat } from "@/types";
+import type {
+  MediaType,
+  ConversionSettings,
+  SourceActionType,
+} from "@/types";
+import { getQualityConfigForFormat, DEFAULT_ORIGINALS_DIRECTORY } from "@/types";
+
+const SOURCE_ACTIONS: { value: SourceActionType; label: string }[] = [
+  { value: "leave", label: "Leave source in place" },
+  { value: "trash", label: "Move source to trash" },
+  { value: "move", label: "Move source to originals folder" },
+  { value: "delete", label: "Delete source" },
+];
 
 interface SettingsPanelProps {
   settings: ConversionSettings;
@@ -350,6 +361,52 @@ export function SettingsPanel({
               {outputDirectory || "Source directory"}
             </span>
           </button>
+          {outputDirectory && (
+            <label className="flex items-center justify-between cursor-pointer">
+              <span className="text-[10px] text-ash">
+                Preserve folder structure
+              </span>
+              <ToggleSwitch
+                checked={settings.preserveStructure ?? false}
+                onChange={(checked) =>
+                  onSettingsChange({ preserveStructure: checked })
+                }
+                size="sm"
+              />
+            </label>
+          )}
+          <div className="space-y-1.5">
+            <span className="text-[10px] text-ash block">
+              After a successful conversion
+            </span>
+            <select
+              value={settings.sourceAction ?? "leave"}
+              onChange={(e) =>
+                onSettingsChange({
+                  sourceAction: e.target.value as SourceActionType,
+                })
+              }
+              className="w-full px-1.5 py-1 text-[10px] bg-slate/50 border border-border-subtle rounded text-snow focus:border-neon-cyan/50 focus:outline-none"
+            >
+              {SOURCE_ACTIONS.map((a) => (
+                <option key={a.value} value={a.value}>
+                  {a.label}
+                </option>
+              ))}
+            </select>
+            {settings.sourceAction === "move" && (
+              <input
+                type="text"
+                value={settings.originalsDirectory ?? ""}
+                onChange={(e) =>
+                  onSettingsChange({ originalsDirectory: e.target.value })
+                }
+                placeholder={DEFAULT_ORIGINALS_DIRECTORY}
+                title="Relative folders are created next to each source file"
+                className="w-full px-1.5 py-1 text-[10px] font-mono bg-slate/50 border border-border-subtle rounded text-snow focus:border-neon-cyan/50 focus:outline-none placeholder:text-ash/50"
+              />
+            )}
+          </div>
         </div>
       )}
 
@@ -3,4 +3,5 @@ export { QualityConfig } from "./QualityConfig";
 export { PresetSelector } from "./PresetSelector";
 export { ResizeConfig } from "./ResizeConfig";
 export { NamingConfig } from "./NamingConfig";
+export { RenameRules } from "./RenameRules";
 export { SettingsPanel } from "./SettingsPanel";
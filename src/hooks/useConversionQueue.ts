@@ -1,17 +1,29 @@
 import { useEffect, useCallback, useRef } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { TAURI_COMMANDS } from "@/lib/constants";
-import { computeQueueStats } from "@/lib/utils";
+import {
+  computeQueueStats,
+  formatConversionError,
+  getCommonDirectory,
+} from "@/lib/utils";
 import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 import { useQueueStore } from "@/store/queueStore";
 import { useSettingsStore } from "@/store/settingsStore";
 import { useAppSettingsStore } from "@/store/appSettingsStore";
 import { useToastStore } from "@/store/toastStore";
-import { getDefaultOutputFormat, DEFAULT_RANDOM_LENGTH, getQualityConfigForFormat } from "@/types";
+import {
+  getDefaultOutputFormat,
+  DEFAULT_RANDOM_LENGTH,
+  DEFAULT_COUNTER_PADDING,
+  DEFAULT_TRUNCATE_LENGTH,
+  DEFAULT_ORIGINALS_DIRECTORY,
+  getQualityConfigForFormat,
+} from "@/types";
 import type {
   ConversionSettings,
   ProgressEvent,
   ConversionResult,
+  ConversionError,
   ItemStatus,
 } from "@/types";
 
@@ -74,7 +86,9 @@ export function useConversionQueue(): UseConversionQueueReturn {
 
   const processItem = useCallback(
     async (itemId: string): Promise<ItemStatus> => {
-      const item = useQueueStore.getState().items.find((i) => i.id === itemId);
+      const items = useQueueStore.getState().items;
+      const itemIndex = items.findIndex((i) => i.id === itemId);
+      const item = items[itemIndex];
       if (!item) return "error";
 
       currentItemRef.current = itemId;
@@ -163,17 +177,84 @@ export function useConversionQueue(): UseConversionQueueReturn {
                   };
                 case "date":
                   return { type: "date" };
+                case "counter":
+                  return {
+                    type: "counter",
+                    start: block.params?.start ?? 1,
+                    step: block.params?.step ?? 1,
+                    padding: block.params?.padding ?? DEFAULT_COUNTER_PADDING,
+                  };
+                case "format":
+                case "extension":
+                  return { type: block.type };
+                case "resolution":
+                case "duration":
+                case "preset":
+                case "parent_folder":
+                  return {
+                    type: block.type,
+                    fallback: block.params?.fallback || "",
+                  };
+                case "metadata":
+                  return {
+                    type: "metadata",
+                    field: block.params?.field || "date_taken",
+                    fallback: block.params?.fallback || "",
+                  };
                 default:
                   return { type: "original" };
               }
             }),
             sanitize_enabled: mergedSettings.namingConfig.sanitizeEnabled,
+            sanitize_mode: mergedSettings.namingConfig.sanitizeMode ?? "ascii",
+            preserve_case: mergedSettings.namingConfig.preserveCase ?? false,
+            separator: mergedSettings.namingConfig.separator ?? "_",
+            template: mergedSettings.namingConfig.template?.trim() || null,
+            rules: (mergedSettings.namingConfig.rules ?? []).map((rule) => {
+              switch (rule.type) {
+                case "replace":
+                  return {
+                    type: "replace",
+                    find: rule.find || "",
+                    replace: rule.replace || "",
+                  };
+                case "regex":
+                  return {
+                    type: "regex",
+                    pattern: rule.pattern || "",
+                    replace: rule.replace || "",
+                  };
+                case "trim":
+                  return { type: "trim", chars: rule.chars || "" };
+                case "truncate":
+                  return {
+                    type: "truncate",
+                    max_length: rule.maxLength || DEFAULT_TRUNCATE_LENGTH,
+                  };
+                case "case":
+                  return { type: "case", case: rule.case || "lower" };
+              }
+            }),
           },
           output_directory: mergedSettings.outputDirectory || null,
           conflict_mode: useAppSettingsStore.getState().conflictMode || "skip",
           processing_enabled: mergedSettings.processingEnabled ?? true,
           max_bitrate: mergedSettings.maxBitrate || null,
           video_preset: item.mediaType === "video" ? mergedSettings.videoPreset : null,
+          batch_index: itemIndex,
+          preserve_structure: mergedSettings.preserveStructure ?? false,
+          base_directory: mergedSettings.preserveStructure
+            ? getCommonDirectory(items.map((i) => i.inputPath))
+            : null,
+          source_action:
+            mergedSettings.sourceAction === "move"
+              ? {
+                action: "move",
+                directory:
+                  mergedSettings.originalsDirectory?.trim() ||
+                  DEFAULT_ORIGINALS_DIRECTORY,
+              }
+              : { action: mergedSettings.sourceAction ?? "leave" },
         };
 
         const result = await invoke<ConversionResult>(
@@ -181,17 +262,32 @@ export function useConversionQueue(): UseConversionQueueReturn {
           { request },
         );
 
-        if (result.success && result.outputPath) {
+        if (result.skipped) {
+          updateStatus(itemId, "conflict", "Skipped: output already exists");
+          finalStatus = "conflict";
+        } else if (result.success && result.outputPath) {
           setOutputPath(itemId, result.outputPath);
           updateStatus(itemId, "completed");
           finalStatus = "completed";
+          if (result.sourceAction && !result.sourceAction.success) {
+            addToast(
+              `${item.fileName}: ${result.sourceAction.errorMessage || "source action failed"}`,
+              "warning",
+            );
+          }
         } else {
           updateStatus(itemId, "error", result.errorMessage || "Unknown error");
           finalStatus = "error";
         }
       } catch (error) {
-        const message = error instanceof Error ? error.message : String(error);
-        if (message.includes("Conversion cancelled")) {
+        const conversionError = error as ConversionError;
+        const message =
+          error instanceof Error
+            ? error.message
+            : conversionError?.type
+              ? formatConversionError(conversionError)
+              : String(error);
+        if (conversionError?.type === "Cancelled") {
           updateStatus(itemId, "cancelled", "Cancelled by user");
           finalStatus = "cancelled";
         } else {
@@ -203,7 +299,7 @@ export function useConversionQueue(): UseConversionQueueReturn {
       }
       return finalStatus;
     },
-    [getMergedSettings, updateStatus, setOutputPath],
+    [getMergedSettings, updateStatus, setOutputPath, addToast],
   );
 
   const processQueue = useCallback(async () => {
@@ -278,7 +374,7 @@ export function useConversionQueue(): UseConversionQueueReturn {
     listen<ProgressEvent>("conversion-progress", (event) => {
       const { id, progress, status, message } = event.payload;
       updateProgress(id, progress);
-      if (status === "error" || status === "cancelled") {
+      if (status === "error" || status === "cancelled" || status === "conflict") {
         updateStatus(id, status, message);
       }
     }).then((unlisten) => unlistenFns.push(unlisten));
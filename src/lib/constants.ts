@@ -46,10 +46,20 @@ export const TAURI_COMMANDS = {
   CANCEL_CONVERSION: "cancel_conversion",
   CONVERT_FILE: "convert_file",
   CHECK_FILE_EXISTS: "check_file_exists",
+  SCAN_DIRECTORY: "scan_directory",
+  CANCEL_SCAN: "cancel_scan",
+  LIST_WATCH_FOLDERS: "list_watch_folders",
+  CREATE_WATCH_FOLDER: "create_watch_folder",
+  UPDATE_WATCH_FOLDER: "update_watch_folder",
+  DELETE_WATCH_FOLDER: "delete_watch_folder",
 } as const;
 
 export const TAURI_EVENTS = {
   DRAG_ENTER: "tauri://drag-enter",
   DRAG_LEAVE: "tauri://drag-leave",
   DRAG_DROP: "tauri://drag-drop",
+  SCAN_PROGRESS: "scan-progress",
+  WATCH_EVENT: "watch-event",
 } as const;
+
+export const FOLDER_SCAN_MAX_DEPTH = 16;
@@ -1,5 +1,6 @@
 import { clsx, type ClassValue } from "clsx";
 import { twMerge } from "tailwind-merge";
+import type { ConversionError } from "@/types";
 
 export function cn(...inputs: ClassValue[]) {
   return twMerge(clsx(inputs));
@@ -40,6 +41,24 @@ export function getFileNameWithoutExtension(filePath: string): string {
   return lastDot > 0 ? fileName.substring(0, lastDot) : fileName;
 }
 
+export function getCommonDirectory(filePaths: string[]): string | null {
+  const dirs = filePaths.map((p) => p.split(/[\\/]/).slice(0, -1));
+  if (dirs.length === 0) return null;
+
+  let length = dirs[0].length;
+  for (const dir of dirs.slice(1)) {
+    let i = 0;
+    while (i < length && i < dir.length && dir[i] === dirs[0][i]) i++;
+    length = i;
+  }
+  if (length === 0) return null;
+
+  const separator = filePaths[0].includes("\\") ? "\\" : "/";
+  // A lone root ("" on Unix, "C:" on Windows) needs its trailing separator.
+  if (length === 1) return dirs[0][0] + separator;
+  return dirs[0].slice(0, length).join(separator);
+}
+
 export function truncate(str: string, maxLength: number): string {
   if (str.length <= maxLength) return str;
   return str.substring(0, maxLength - 3) + "...";
@@ -125,3 +144,10 @@ export function computeQueueStats(items: { status: string; fileSize?: number }[]
     totalSize: successSize + cancelledSize + errorSize + pendingSize + processingSize,
   };
 }
+
+export function formatConversionError(error: ConversionError): string {
+  const details = Object.values(error.details ?? {})
+    .filter((value) => value !== null && value !== undefined && value !== "")
+    .map((value) => (Array.isArray(value) ? value.join(", ") : String(value)));
+  return details.length > 0 ? `${error.type}: ${details.join(" - ")}` : error.type;
+}
@@ -1,6 +1,6 @@
 export type VideoFormat = "mp4" | "mkv" | "mov" | "webm";
 export type ImageFormat = "jpg" | "png" | "webp";
-export type AudioFormat = "mp3" | "aac" | "m4a" | "ogg";
+export type AudioFormat = "mp3" | "aac" | "m4a" | "ogg" | "flac";
 export type OutputFormat = VideoFormat | ImageFormat | AudioFormat;
 
 export type MediaType = "video" | "image" | "audio";
@@ -9,9 +9,29 @@ export type ResizeMode = "fill" | "cover" | "contain";
 
 export type BackgroundColor = "transparent" | "black" | "white";
 
-export type ConflictMode = "skip" | "overwrite" | "keep_both";
-
-export type NamingBlockType = "original" | "prefix" | "random" | "date";
+export type ConflictMode =
+  | "skip"
+  | "overwrite"
+  | "overwrite_if_older"
+  | "overwrite_if_larger"
+  | "overwrite_if_smaller"
+  | "keep_both";
+
+export type NamingBlockType =
+  | "original"
+  | "prefix"
+  | "random"
+  | "date"
+  | "counter"
+  | "resolution"
+  | "duration"
+  | "format"
+  | "preset"
+  | "extension"
+  | "parent_folder"
+  | "metadata";
+
+export type MetadataField = "date_taken" | "title" | "artist" | "album" | "camera";
 
 export interface NamingBlock {
   id: string;
@@ -19,12 +39,39 @@ export interface NamingBlock {
   params?: {
     value?: string;
     length?: number;
+    start?: number;
+    step?: number;
+    padding?: number;
+    field?: MetadataField;
+    fallback?: string;
   };
 }
 
+export type CaseTransform = "lower" | "upper" | "title" | "kebab" | "snake";
+
+export type RenameRuleType = "replace" | "regex" | "trim" | "truncate" | "case";
+
+export interface RenameRule {
+  id: string;
+  type: RenameRuleType;
+  find?: string;
+  pattern?: string;
+  replace?: string;
+  chars?: string;
+  maxLength?: number;
+  case?: CaseTransform;
+}
+
+export type SanitizeMode = "ascii" | "transliterate" | "unicode";
+
 export interface NamingConfig {
   blocks: NamingBlock[];
   sanitizeEnabled: boolean;
+  sanitizeMode?: SanitizeMode;
+  preserveCase?: boolean;
+  separator?: string;
+  template?: string;
+  rules?: RenameRule[];
 }
 
 export type ProcessStatus =
@@ -35,7 +82,7 @@ export type ProcessStatus =
   | "cancelled"
   | "conflict";
 
-export type ItemStatus = "completed" | "error" | "cancelled";
+export type ItemStatus = "completed" | "error" | "cancelled" | "conflict";
 
 export interface ResizeConfig {
   width: number;
@@ -64,12 +111,77 @@ export interface ConversionSettings {
   stripMetadata: boolean;
   namingConfig: NamingConfig;
   outputDirectory?: string;
+  preserveStructure?: boolean;
+  sourceAction?: SourceActionType;
+  originalsDirectory?: string;
   conflictMode: ConflictMode;
   processingEnabled: boolean;
   maxBitrate: number | null;
   videoPreset: string;
 }
 
+export interface ScannedFile {
+  path: string;
+  name: string;
+  size: number;
+  mediaType: MediaType;
+}
+
+export interface ScanChunk {
+  id: string;
+  files: ScannedFile[];
+  scannedFiles: number;
+}
+
+export interface ScanSummary {
+  id: string;
+  found: number;
+  scannedFiles: number;
+  cancelled: boolean;
+}
+
+export type SourceActionType = "leave" | "trash" | "move" | "delete";
+
+export type SourceAction =
+  | { action: "leave" }
+  | { action: "trash" }
+  | { action: "move"; directory: string }
+  | { action: "delete" };
+
+export interface SourceActionReport {
+  action: SourceAction;
+  success: boolean;
+  movedTo?: string;
+  errorMessage?: string;
+}
+
+export interface WatchFolderSettings {
+  path: string;
+  preset_id: string;
+  output_directory: string;
+  recursive: boolean;
+  enabled: boolean;
+  conflict_mode: ConflictMode;
+  settle_secs: number;
+  source_action: SourceAction;
+}
+
+export interface WatchFolder {
+  id: string;
+  settings: WatchFolderSettings;
+}
+
+export type WatchEventKind = "detected" | "converted" | "skipped" | "failed";
+
+export interface WatchEvent {
+  watchId: string;
+  jobId: string;
+  path: string;
+  kind: WatchEventKind;
+  outputPath?: string;
+  message?: string;
+}
+
 export type ThumbnailStatus = "pending" | "loading" | "loaded" | "error";
 
 export interface QueueItem {
@@ -98,8 +210,61 @@ export interface ProgressEvent {
 export interface ConversionResult {
   id: string;
   success: boolean;
+  skipped: boolean;
   outputPath?: string;
   errorMessage?: string;
+  verification?: VerificationReport;
+  stats?: ConversionStats;
+  oversize?: OversizeReport;
+  sourceAction?: SourceActionReport;
+}
+
+export type OversizePolicy = "copy_original" | "keep_original_format" | "flag";
+
+export interface OversizeReport {
+  policy: OversizePolicy;
+  thresholdPercent: number;
+  inputBytes: number;
+  outputBytes: number;
+}
+
+export interface ConversionStats {
+  inputBytes: number;
+  outputBytes: number;
+  compressionRatio?: number;
+  elapsedMs: number;
+  speed?: number;
+  width?: number;
+  height?: number;
+  durationSecs?: number;
+}
+
+export interface BatchSummary {
+  total: number;
+  succeeded: number;
+  failed: number;
+  skipped: number;
+  cancelled: number;
+  inputBytes: number;
+  outputBytes: number;
+  compressionRatio?: number;
+  elapsedMs: number;
+}
+
+export interface VerificationCheck {
+  kind: "streams" | "duration" | "decode";
+  passed: boolean;
+  message: string;
+}
+
+export interface VerificationReport {
+  passed: boolean;
+  checks: VerificationCheck[];
+}
+
+export interface ConversionError {
+  type: string;
+  details?: Record<string, unknown>;
 }
 
 export const VIDEO_EXTENSIONS = [
@@ -143,7 +308,7 @@ export const ALL_EXTENSIONS = [
 export const DISPLAY_FORMATS = {
   video: ["MP4", "MKV", "MOV", "WebM"],
   image: ["JPG", "PNG", "WebP"],
-  audio: ["MP3", "AAC", "M4A", "OGG"],
+  audio: ["MP3", "AAC", "M4A", "OGG", "FLAC"],
 } as const;
 
 export const VIDEO_OUTPUT_FORMATS: VideoFormat[] = [
@@ -155,7 +320,7 @@ export const VIDEO_OUTPUT_FORMATS: VideoFormat[] = [
 
 export const IMAGE_OUTPUT_FORMATS: ImageFormat[] = ["jpg", "png", "webp"];
 
-export const AUDIO_OUTPUT_FORMATS: AudioFormat[] = ["mp3", "aac", "m4a", "ogg"];
+export const AUDIO_OUTPUT_FORMATS: AudioFormat[] = ["mp3", "aac", "m4a", "ogg", "flac"];
 
 export function getMediaType(extension: string): MediaType | null {
   const ext = extension.toLowerCase().replace(".", "");
@@ -193,6 +358,9 @@ export function getDefaultOutputFormat(mediaType: MediaType): OutputFormat {
 }
 
 export const DEFAULT_RANDOM_LENGTH = 8;
+export const DEFAULT_COUNTER_PADDING = 3;
+export const DEFAULT_TRUNCATE_LENGTH = 64;
+export const DEFAULT_ORIGINALS_DIRECTORY = "originals";
 
 export interface FormatQualityInfo {
   min: number;
@@ -221,6 +389,7 @@ export const FORMAT_QUALITY_CONFIG: Record<string, FormatQualityInfo> = {
   aac: { min: 128, max: 320, default: 192, step: 8, label: "Bitrate", unit: "kbps", description: "Higher = higher quality", lowerIsBetter: false },
   m4a: { min: 128, max: 320, default: 192, step: 8, label: "Bitrate", unit: "kbps", description: "Higher = higher quality", lowerIsBetter: false },
   ogg: { min: 1, max: 8, default: 6, step: 1, label: "Quality", unit: "", description: "Higher = higher quality", lowerIsBetter: false },
+  flac: { min: 0, max: 0, default: 0, step: 0, label: "Lossless", unit: "", description: "No quality setting needed", lowerIsBetter: false, isLossless: true },
 };
 
 export function getQualityConfigForFormat(format: string | null): FormatQualityInfo | null {